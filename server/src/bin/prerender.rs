@@ -0,0 +1,186 @@
+//! Renders every route of the tutorial ahead of time, into static files that
+//! any file host can serve: `dist/index.html`, `dist/contacts/index.html`,
+//! `dist/contacts/alice/index.html` and so on, along with a `404.html` and a
+//! `sitemap.xml`. The routes with params, like `/contacts/:id`, are filled in
+//! by the app they belong to, from its `static_paths`.
+//!
+//! Every page is rendered in full, with the data of its resources already in it,
+//! and the client built from `hydrate.html` takes it over once it has loaded.
+//!
+//! From the repository root, with the URL the site will be served from:
+//!
+//! ```sh
+//! trunk build hydrate.html --dist dist
+//! cargo run --manifest-path server/Cargo.toml --bin prerender -- https://example.com
+//! ```
+use std::{collections::BTreeSet, fmt::Write, fs, path::PathBuf};
+
+use futures::StreamExt;
+use leptos::*;
+use leptos_router::generate_route_list_inner;
+use leptos_tutorial::{
+    apps,
+    locale::{localize_path, Locale},
+    tutorial::RouteManager,
+};
+use leptos_tutorial_server::{provide_server_context, RedirectTo, Shell};
+use tokio::task::LocalSet;
+
+// where `trunk build hydrate.html --dist dist` puts the client, and the pages go
+const DIST: &str = "dist";
+// any path none of the routes match, for the 404 page
+const NOT_FOUND: &str = "/404";
+
+// what rendering a path came to
+enum Page {
+    // a <Redirect/> ran, e.g. in a protected route
+    Redirect(String),
+    Html(String),
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let site_url = std::env::args()
+        .nth(1)
+        .or_else(|| std::env::var("SITE_URL").ok())
+        .unwrap_or_else(|| "http://localhost:8080".to_string());
+    let site_url = site_url.trim_end_matches('/');
+
+    // the pages are written back in between <body> and </body>, so this
+    // is still the same shell when the prerender runs a second time
+    let html = fs::read_to_string(format!("{DIST}/index.html"))
+        .unwrap_or_else(|_| panic!("the client to have been built into {DIST}, see hydrate.html"));
+    let shell = Shell::parse(&html);
+
+    let paths = static_paths();
+    let mut sitemap = Vec::new();
+    // the reactive system isn't Send, so everything renders on this one thread
+    LocalSet::new()
+        .run_until(async {
+            for path in &paths {
+                match render(&shell, site_url, path).await {
+                    Page::Html(html) => {
+                        write_page(&page_file(path), &html);
+                        sitemap.push(path.clone());
+                    }
+                    // left out of the sitemap, it isn't a page of its own
+                    Page::Redirect(to) => write_page(&page_file(path), &redirect_page(&to)),
+                }
+                println!("{path}");
+            }
+            // file hosts answer every path they don't have with this, and the
+            // client then routes to whatever the path was
+            if let Page::Html(html) = render(&shell, site_url, NOT_FOUND).await {
+                write_page(&PathBuf::from(format!("{DIST}/404.html")), &html);
+            }
+        })
+        .await;
+
+    write_page(&PathBuf::from(format!("{DIST}/sitemap.xml")), &sitemap_xml(site_url, &sitemap));
+    println!("{} pages in {DIST}/", paths.len());
+}
+
+/// Every path to prerender: each route as it is, and each route with params
+/// once for every value the app it belongs to has for them, in every locale.
+fn static_paths() -> BTreeSet<String> {
+    let (routes, _) = generate_route_list_inner(|| view! { <RouteManager/> });
+
+    let runtime = create_runtime();
+    // the apps fill in their params from the data they provide
+    for app in apps::REGISTRY {
+        (app.provide)();
+    }
+    let paths = routes
+        .iter()
+        .map(|route| route.path())
+        // a wildcard matches anything, so there's nothing to render for it; 404.html covers it
+        .filter(|pattern| !pattern.contains('*'))
+        // the localized routes are copies of these, so their paths are worked out from them below
+        .filter(|pattern| Locale::of(pattern) == Locale::En)
+        .flat_map(|pattern| {
+            let paths = if pattern.contains(':') {
+                apps::REGISTRY
+                    .iter()
+                    .filter(|app| pattern.starts_with(app.base_path))
+                    .flat_map(|app| (app.static_paths)(pattern))
+                    .collect()
+            } else {
+                vec![pattern.to_string()]
+            };
+            if paths.is_empty() {
+                // like a renamed URL's old pattern, which only redirects. said out loud, so a
+                // route that should have pages doesn't go missing from dist/ without anyone noticing
+                eprintln!("skipped {pattern}: no app fills in its params");
+            }
+            Locale::ALL
+                .iter()
+                .flat_map(|&locale| paths.iter().map(move |path| localize_path(pattern, path, locale)))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    runtime.dispose();
+    paths
+}
+
+async fn render(shell: &Shell, site_url: &str, path: &str) -> Page {
+    let redirect = RedirectTo::default();
+
+    // in order: unlike the server, this waits for every <Suspense/> to resolve,
+    // so the file has the whole page in it and not just the fallbacks
+    let (stream, runtime) = leptos::ssr::render_to_stream_in_order_with_prefix_undisposed_with_context(
+        || view! { <RouteManager/> }.into_view(),
+        || "".into(),
+        {
+            let (site_url, path, redirect) = (site_url.to_string(), path.to_string(), RedirectTo::clone(&redirect));
+            move || provide_server_context(&site_url, &path, &redirect)
+        },
+    );
+    let body = stream.collect::<String>().await;
+    runtime.dispose();
+
+    let redirect = redirect.borrow_mut().take();
+    match redirect {
+        Some(to) => Page::Redirect(to),
+        None => Page::Html(format!("{}{body}{}", shell.head, shell.tail)),
+    }
+}
+
+// `/` is dist/index.html, `/contacts/alice` is dist/contacts/alice/index.html
+fn page_file(path: &str) -> PathBuf {
+    let mut file = PathBuf::from(DIST);
+    file.extend(path.split('/').filter(|segment| !segment.is_empty()));
+    file.join("index.html")
+}
+
+fn write_page(file: &PathBuf, contents: &str) {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir).unwrap_or_else(|error| panic!("to create {}: {error}", dir.display()));
+    }
+    fs::write(file, contents).unwrap_or_else(|error| panic!("to write {}: {error}", file.display()));
+}
+
+// a file host can't answer with a redirect, so the page does it
+fn redirect_page(to: &str) -> String {
+    let to = escape(to);
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta http-equiv=\"refresh\" content=\"0; url={to}\">\
+         <link rel=\"canonical\" href=\"{to}\"></head><body><a href=\"{to}\">{to}</a></body></html>\n"
+    )
+}
+
+fn sitemap_xml(site_url: &str, paths: &[String]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+    for path in paths {
+        _ = writeln!(xml, "  <url><loc>{}</loc></url>", escape(&format!("{site_url}{path}")));
+    }
+    xml.push_str("</urlset>\n");
+    xml
+}
+
+// for text in an attribute or an XML element
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,35 @@
+//! What the server and the prerender have in common: the page trunk built, and
+//! the context the router needs to render a path away from the browser.
+use std::{cell::RefCell, rc::Rc};
+
+use leptos::*;
+use leptos_router::{provide_server_redirect, RouterIntegrationContext, ServerIntegration};
+
+/// The page trunk built, cut open where the rendered app goes.
+pub struct Shell {
+    /// everything up to and including `<body>`, with the client's `<script>` in it
+    pub head: String,
+    /// `</body></html>`
+    pub tail: String,
+}
+
+impl Shell {
+    pub fn parse(html: &str) -> Self {
+        let body = html.find("<body").expect("index.html to have a <body>");
+        let open_end = body + html[body..].find('>').expect("<body> to be closed") + 1;
+        let close = html.rfind("</body>").expect("index.html to have a </body>");
+        Self { head: html[..open_end].to_string(), tail: html[close..].to_string() }
+    }
+}
+
+/// Where a <Redirect/> in the rendered page wanted to go, if it ran.
+pub type RedirectTo = Rc<RefCell<Option<String>>>;
+
+/// Makes the next <Router/> render `path`, like `/contacts?q=bob`, and note any
+/// redirect in `redirect`. Call it in the context function of a render.
+pub fn provide_server_context(origin: &str, path: &str, redirect: &RedirectTo) {
+    // the router reads the URL from this instead of the browser's location
+    provide_context(RouterIntegrationContext::new(ServerIntegration { path: format!("{origin}{path}") }));
+    let redirect = Rc::clone(redirect);
+    provide_server_redirect(move |to| *redirect.borrow_mut() = Some(to.to_string()));
+}
@@ -0,0 +1,123 @@
+//! Renders the tutorial on the server, so the first paint already has the page
+//! in it instead of an empty `<body>`. The client built from `hydrate.html` then
+//! takes over that HTML, and the resources the server started loading (like the
+//! ones in `AsyncComponent` and `SuspenseComponent`) stream in as they resolve.
+//!
+//! From the repository root:
+//!
+//! ```sh
+//! trunk build hydrate.html --dist target/site
+//! cargo run --manifest-path server/Cargo.toml
+//! ```
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::Uri,
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+    Router,
+};
+use futures::StreamExt;
+use leptos::*;
+use leptos_tutorial::tutorial::RouteManager;
+use leptos_tutorial_server::{provide_server_context, RedirectTo, Shell};
+use tokio::sync::mpsc;
+use tokio_util::task::LocalPoolHandle;
+use tower_http::services::ServeDir;
+
+// where `trunk build hydrate.html --dist target/site` puts the client
+const SITE_ROOT: &str = "target/site";
+const ADDRESS: &str = "127.0.0.1:3000";
+
+#[derive(Clone)]
+struct AppState {
+    shell: Arc<Shell>,
+    // the reactive system isn't Send, so every page renders on a thread of its own
+    pool: LocalPoolHandle,
+}
+
+#[tokio::main]
+async fn main() {
+    let html = tokio::fs::read_to_string(format!("{SITE_ROOT}/index.html"))
+        .await
+        .unwrap_or_else(|_| panic!("the client to have been built into {SITE_ROOT}, see hydrate.html"));
+    let state = AppState {
+        shell: Arc::new(Shell::parse(&html)),
+        pool: LocalPoolHandle::new(std::thread::available_parallelism().map_or(1, usize::from)),
+    };
+
+    // the files trunk built are served as they are; every other path is a page.
+    // index.html itself is only the shell, so it's never served on its own
+    let files = ServeDir::new(SITE_ROOT)
+        .append_index_html_on_directories(false)
+        .fallback(get(render_page).with_state(state));
+    let app = Router::new().fallback_service(files);
+
+    let listener = tokio::net::TcpListener::bind(ADDRESS).await.expect("the address to be free");
+    println!("listening on http://{ADDRESS}");
+    axum::serve(listener, app).await.expect("the server to keep running");
+}
+
+// what the rendering thread sends back
+enum Rendered {
+    // a protected route sent us elsewhere before anything was written
+    Redirect(String),
+    Html(String),
+}
+
+async fn render_page(State(state): State<AppState>, uri: Uri) -> Response {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let path = uri.path_and_query().map_or_else(|| "/".to_string(), ToString::to_string);
+    state.pool.spawn_pinned(move || render(state.shell, path, tx));
+
+    match rx.recv().await {
+        Some(Rendered::Redirect(to)) => Redirect::temporary(&to).into_response(),
+        Some(Rendered::Html(first)) => {
+            let rest = futures::stream::unfold(rx, |mut rx| async move {
+                match rx.recv().await {
+                    Some(Rendered::Html(chunk)) => Some((chunk, rx)),
+                    _ => None,
+                }
+            });
+            let chunks = futures::stream::once(async { first }).chain(rest).map(Ok::<_, Infallible>);
+            ([("content-type", "text/html; charset=utf-8")], Body::from_stream(chunks)).into_response()
+        }
+        None => (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "the page failed to render").into_response(),
+    }
+}
+
+async fn render(shell: Arc<Shell>, path: String, tx: mpsc::UnboundedSender<Rendered>) {
+    let redirect = RedirectTo::default();
+
+    // out of order: the page goes out right away, with the fallback of every
+    // <Suspense/> that's still loading, and each one is swapped in as it resolves
+    let (stream, runtime) = leptos::ssr::render_to_stream_with_prefix_undisposed_with_context(
+        || view! { <RouteManager/> }.into_view(),
+        {
+            let head = shell.head.clone();
+            move || head.into()
+        },
+        {
+            let redirect = RedirectTo::clone(&redirect);
+            move || provide_server_context(&format!("http://{ADDRESS}"), &path, &redirect)
+        },
+    );
+
+    // the view has been rendered by now, so any <Redirect/> in it has already run
+    let redirect = redirect.borrow_mut().take();
+    if let Some(to) = redirect {
+        _ = tx.send(Rendered::Redirect(to));
+    } else {
+        let mut stream = Box::pin(stream);
+        while let Some(chunk) = stream.next().await {
+            if tx.send(Rendered::Html(chunk)).is_err() {
+                // the browser went away
+                break;
+            }
+        }
+        _ = tx.send(Rendered::Html(shell.tail.clone()));
+    }
+    runtime.dispose();
+}
@@ -1 +1,86 @@
-pub mod contacts;
\ No newline at end of file
+use leptos::*;
+
+pub mod contacts;
+
+use crate::locale::A;
+use crate::routing::RoutingMode;
+
+/// Everything the tutorial needs to know to host an app: what to call it, where
+/// it lives, and how to render it either inside the tutorial or on its own.
+pub struct AppEntry {
+    /// shown in the nav and the launcher, and used to find the app for a standalone build
+    pub name: &'static str,
+    /// an emoji, shown next to the name
+    pub icon: &'static str,
+    /// where the app's routes start, e.g. `/contacts`
+    pub base_path: &'static str,
+    /// the app's pages worth suggesting when someone mistypes a URL
+    pub pages: &'static [&'static str],
+    /// whether only signed in users may see the app's routes
+    pub requires_login: bool,
+    /// provides whatever context the app's routes read. call it above the <Router/>
+    pub provide: fn(),
+    /// registers what links to the app's routes prefetch, after `provide_loaders`
+    pub prefetch: fn(),
+    /// the app's routes, to go inside someone else's <Routes/>. each one has to be a
+    /// <Route/>, or a transparent component returning one
+    pub routes: fn() -> Vec<View>,
+    /// the app on its own, with its own <Router/>, for a standalone bundle
+    pub root: fn() -> View,
+    /// every URL behind one of the app's route patterns with params, like
+    /// `/contacts/:id`, for the prerender. called with the app's context provided
+    pub static_paths: fn(&str) -> Vec<String>,
+}
+
+/// Every app the tutorial hosts. `RouteManager` builds its nav and routes from this.
+pub static REGISTRY: &[AppEntry] = &[AppEntry {
+    name: "contacts",
+    icon: "📇",
+    base_path: "/contacts",
+    pages: contacts::PAGES,
+    requires_login: true,
+    provide: contacts::provide_contact_repositories,
+    prefetch: contacts::provide_contact_prefetches,
+    routes: || {
+        vec![
+            contacts::ContactRoutes().into_view(),
+            contacts::TaggedContactRoutes().into_view(),
+        ]
+    },
+    root: || contacts::ContactApp().into_view(),
+    static_paths: contacts::static_paths,
+}];
+
+/// the registered app called `name`, if there is one
+pub fn find(name: &str) -> Option<&'static AppEntry> {
+    REGISTRY.iter().find(|app| app.name == name)
+}
+
+/// The `main` of a standalone bundle: mounts the app called `name`, and nothing else.
+///
+/// Each app gets a tiny binary in `src/bin/` calling this, and its own html file
+/// pointing trunk at that binary, e.g. `trunk serve contacts.html`.
+pub fn mount_standalone(name: &str) {
+    console_error_panic_hook::set_once();
+    let app = find(name).unwrap_or_else(|| panic!("no app called {name:?} is registered"));
+    mount_to_body(move || {
+        // a bundle is hosted on its own just as often, so it picks its routing mode too
+        RoutingMode::detect().provide();
+        (app.root)()
+    })
+}
+
+/// A link to every registered app, as a grid of tiles.
+#[component]
+pub fn Launcher() -> impl IntoView {
+    view! {
+        <div class="launcher">
+            {REGISTRY.iter().map(|app| view! {
+                <A href=app.base_path class="launcher-tile">
+                    <span class="launcher-icon">{app.icon}</span>
+                    <span class="launcher-name">{app.name}</span>
+                </A>
+            }).collect_view()}
+        </div>
+    }
+}
@@ -1,79 +1,499 @@
+use std::{collections::BTreeSet, rc::Rc};
+
 use leptos::*;
 use leptos_router::*;
 
+pub mod conversations;
+pub mod csv;
+pub mod duplicates;
+pub mod files;
+pub mod form;
+pub mod import_export;
+pub mod model;
+pub mod pagination;
+pub mod repository;
+pub mod schema;
+pub mod search;
+pub mod storage;
+pub mod tags;
+pub mod vcard;
+pub mod virtual_list;
+
+use conversations::{load_conversation, load_thread, Conversations, UnreadCount};
+use duplicates::{find_duplicates, DuplicateList, MergeContacts};
+use form::{DeleteContact, EditContact, NewContact};
+use import_export::{CsvExport, CsvImport, ExportContact, VCardTools};
+use model::{format_timestamp, Contact};
+use pagination::{page_range, use_page, Pager};
+use repository::{
+    provide_contact_repository, provide_conversation_repository, use_contact_repository,
+    InMemoryContactRepository, InMemoryConversationRepository,
+};
+use search::{
+    best_match, keep_query, search, use_search_query, with_query_param, Highlight, MatchedField, SearchBox,
+};
+use storage::{LocalStorage, MemoryStorage, Storage};
+use tags::{tag_href, use_tag, TagSidebar};
+use virtual_list::VirtualList;
+
+use crate::auth::{provide_session, LoginPage, ProtectedRoute, RequireRole, Role, SessionStatus};
+use crate::breadcrumbs::{provide_breadcrumbs, BreadcrumbRoute, Breadcrumbs, CrumbLabel};
+use crate::components::error_handling::ErrorList;
+use crate::loaders::{provide_loaders, provide_prefetch, Prefetch};
+use crate::locale::A;
+use crate::not_found::{NotFound, NotFoundBoundary, NotFoundPage};
+use crate::params::{use_typed_params, Id, InvalidParams};
+use crate::routing::RoutingMode;
+use crate::transition_outlet::{OutletAnimation, TransitionOutlet};
+
+/// The contact app's pages, for the 404 page's suggestions.
+pub const PAGES: &[&str] = &["/login", "/contacts", "/contacts/new", "/contacts/import", "/contacts/duplicates"];
+
+// every row in the contact list is this tall, which is what lets <VirtualList/> work out what's visible
+const ROW_HEIGHT: f64 = 28.0;
+// and the list scrolls inside a box this tall
+const LIST_HEIGHT: f64 = 400.0;
+
+/// Provides the repositories every contact route reads from, kept in localStorage
+/// and filled with sample data the first time.
+///
+/// Call this above the <Router/> so all routes share the same contacts and threads.
+pub fn provide_contact_repositories() {
+    let storage: Rc<dyn Storage> = match LocalStorage::new() {
+        Some(storage) => Rc::new(storage),
+        None => {
+            // the server has never had localStorage, and only ever shows the sample data
+            if !leptos_dom::is_server() {
+                logging::warn!("localStorage isn't available, contacts won't survive a reload");
+            }
+            Rc::new(MemoryStorage::new())
+        }
+    };
+    provide_contact_repository(InMemoryContactRepository::stored(
+        storage.clone(),
+        InMemoryContactRepository::sample_data,
+    ));
+    provide_conversation_repository(InMemoryConversationRepository::stored(
+        storage,
+        InMemoryConversationRepository::sample_data,
+    ));
+}
+
+/// Makes a hovered or focused link to a contact start fetching the thread with
+/// them, so their Conversations tab opens straight away. Call it after [`provide_loaders`].
+pub fn provide_contact_prefetches() {
+    // whichever of the contact's tabs the link goes to, from either list
+    let patterns = [
+        "/contacts/:id",
+        "/contacts/:id/conversations",
+        "/contacts/tag/:tag/:id",
+        "/contacts/tag/:tag/:id/conversations",
+    ];
+    for pattern in patterns {
+        provide_prefetch(pattern, |cache, params, _| {
+            if let Some(id) = params.get("id") {
+                _ = load_thread(cache, id);
+            }
+        });
+    }
+}
+
+/// Every URL behind a parameterized route `pattern`, like each contact for
+/// `/contacts/:id`, for a prerender to write out. The repositories have to have been provided.
+pub fn static_paths(pattern: &str) -> Vec<String> {
+    let contacts = use_contact_repository().list();
+    if pattern.contains(":a") && pattern.contains(":b") {
+        return find_duplicates(&contacts)
+            .into_iter()
+            .map(|pair| pattern.replace(":a", &pair.a.id).replace(":b", &pair.b.id))
+            .collect();
+    }
+    // each contact, under each of its tags when the route has a :tag too
+    contacts
+        .iter()
+        .flat_map(|contact| match (pattern.contains(":tag"), pattern.contains(":id")) {
+            (true, true) => contact.tags.iter().map(|tag| pattern.replace(":tag", tag).replace(":id", &contact.id)).collect(),
+            (true, false) => contact.tags.iter().map(|tag| pattern.replace(":tag", tag)).collect(),
+            (false, true) => vec![pattern.replace(":id", &contact.id)],
+            (false, false) => vec![],
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
 #[component]
 pub fn ContactApp() -> impl IntoView {
+    provide_session();
+    provide_breadcrumbs();
+    provide_loaders();
+    provide_contact_repositories();
+    provide_contact_prefetches();
+
     view! {
         <Router>
+            <Prefetch/>
             <h1>"Contact App"</h1>
             // this <nav> will show on every routes, because its outside the <Routes/>
-            // note: we can just use normal <a> tags and the router will use client-side navigation
+            // note: we can just use normal <a> tags and the router will use client-side navigation.
+            // only the href depends on the routing mode, e.g. `#/contacts` when the app is hosted statically
             <nav>
-                <a href="/">"Home"</a>
-                <a href="/contacts">"Contacts"</a>
+                <a href=RoutingMode::current().href("/")>"Home"</a>
+                <a href=RoutingMode::current().href("/contacts")>"Contacts"</a>
+                <SessionStatus/>
             </nav>
+            <Breadcrumbs/>
             <main>
                 <Routes>
                     // / just has an un-nested "home"
                     <Route path="/" view=|| view! {<h3>"Home"</h3>}/>
-                    // contact has nested routes
-                    <Route path="/contacts" view=ContactList>
-                        // if no id specified, fall back
-                        <Route path=":id" view=ContactInfo>
-                            <Route path="" view=|| view! {<div class="tab">"(Contact Info)"</div>} />
-                            <Route path="conversations" view=|| view! {<div class="tab">"(Conversations)"</div>} />
-                        </Route>
-                        <Route path="" view=|| view! {<div class="select-user">"Select a user to view contact info."</div>} />
-                    </Route>                    
+                    <BreadcrumbRoute label="Log in"><Route path="/login" view=LoginPage/></BreadcrumbRoute>
+                    // contact has nested routes, and you have to log in to see them
+                    <ProtectedRoute><ContactRoutes/></ProtectedRoute>
+                    <ProtectedRoute><TaggedContactRoutes/></ProtectedRoute>
+                    <BreadcrumbRoute label="Not found">
+                        <Route path="/*any" view=|| {
+                            let paths = ["/"].iter().chain(PAGES).map(|path| path.to_string()).collect::<Vec<_>>();
+                            view! { <NotFoundPage paths /> }
+                        }/>
+                    </BreadcrumbRoute>
                 </Routes>
             </main>
         </Router>
     }
 }
 
+/// The whole `/contacts` route tree.
+///
+/// A transparent component just returns its <Route/>, so the same tree can be
+/// dropped into any <Routes/>: here and in the tutorial's `RouteManager`. Use it
+/// together with [`TaggedContactRoutes`].
+#[component(transparent)]
+pub fn ContactRoutes() -> impl IntoView {
+    view! {
+        <BreadcrumbRoute label="Contacts">
+            <Route path="/contacts" view=ContactList>
+                // only editors can add contacts
+                <ProtectedRoute role=Role::Editor>
+                    <BreadcrumbRoute label="New"><Route path="new" view=NewContact/></BreadcrumbRoute>
+                </ProtectedRoute>
+                <ProtectedRoute role=Role::Editor>
+                    <BreadcrumbRoute label="Import"><Route path="import" view=CsvImport/></BreadcrumbRoute>
+                </ProtectedRoute>
+                // a merge rewrites one contact and deletes the other, so finding them is for editors too
+                <ProtectedRoute role=Role::Editor>
+                    <BreadcrumbRoute label="Duplicates"><Route path="duplicates" view=DuplicateList/></BreadcrumbRoute>
+                </ProtectedRoute>
+                <ProtectedRoute role=Role::Editor>
+                    <BreadcrumbRoute label="Merge"><Route path="duplicates/:a/:b" view=MergeContacts/></BreadcrumbRoute>
+                </ProtectedRoute>
+                <ContactDetailRoutes/>
+                <Route path="" view=SelectContact/>
+            </Route>
+        </BreadcrumbRoute>
+    }
+}
+
+/// `/contacts/tag/:tag`: the same list narrowed to one tag, with the same `:id`
+/// routes underneath it.
+///
+/// This is its own top-level route rather than a child of `/contacts` because
+/// <ContactList/> needs to read `:tag`, and a route only sees its own params.
+#[component(transparent)]
+pub fn TaggedContactRoutes() -> impl IntoView {
+    view! {
+        <BreadcrumbRoute label=CrumbLabel::dynamic(|| {
+            let tag = use_tag();
+            Signal::derive(move || tag().ok().flatten().map(|tag| format!("#{tag}")))
+        })>
+            <Route path="/contacts/tag/:tag" view=ContactList>
+                <ContactDetailRoutes/>
+                <Route path="" view=SelectContact/>
+            </Route>
+        </BreadcrumbRoute>
+    }
+}
+
+/// `:id` and its tabs, shared by both contact lists.
+#[component(transparent)]
+fn ContactDetailRoutes() -> impl IntoView {
+    view! {
+        // the crumb is the contact's name, which follows renames
+        <BreadcrumbRoute label=CrumbLabel::dynamic(|| {
+            let repository = use_contact_repository();
+            let params = use_typed_params::<ContactParams>();
+            Signal::derive(move || params().ok().and_then(|params| repository.get(&params.id.0)).map(|contact| contact.name))
+        })>
+            // if no id specified, fall back
+            <Route path=":id" view=ContactInfo data=load_contact>
+                <Route path="" view=ContactDetails/>
+                <BreadcrumbRoute label="Conversations">
+                    <Route path="conversations" view=Conversations data=load_conversation/>
+                </BreadcrumbRoute>
+                <ProtectedRoute role=Role::Editor>
+                    <BreadcrumbRoute label="Edit"><Route path="edit" view=EditContact/></BreadcrumbRoute>
+                </ProtectedRoute>
+            </Route>
+        </BreadcrumbRoute>
+    }
+}
+
+#[component]
+fn SelectContact() -> impl IntoView {
+    view! { <div class="select-user">"Select a user to view contact info."</div> }
+}
+
 #[component]
 pub fn ContactList() -> impl IntoView {
+    let repository = use_contact_repository();
+
+    // the search lives in the URL, so it survives reloads and navigating into a contact
+    let q = use_search_query();
+    // under /contacts/tag/:tag only that tag's contacts are listed (and searched)
+    let tag = use_tag();
+    let results = create_memo(move |_| {
+        let mut contacts = repository.list();
+        if let Ok(Some(tag)) = tag() {
+            contacts.retain(|contact| contact.tags.contains(&tag));
+        }
+        search(contacts, &q())
+    });
+    let page = use_page();
+    // a memo of just whether they parsed, so moving between pages doesn't re-mount the list
+    let params_ok = create_memo(move |_| tag().and(page()).map(|_| ()));
+
     view! {
         <div class="contact-list">
             // here ours contact list component itseld
-            <h3>"Contacts"</h3>
+            <h3>{move || match tag() {
+                Ok(Some(tag)) => format!("Contacts tagged #{tag}"),
+                _ => "Contacts".to_string(),
+            }}</h3>
+            <RequireRole role=Role::Editor>
+                <A href="/contacts/new">"New Contact"</A>
+            </RequireRole>
+            <TagSidebar/>
+            <SearchBox/>
+            // exports whatever the search is currently showing
+            <VCardTools contacts=results />
+            <div class="csv-tools">
+                <RequireRole role=Role::Editor>
+                    <A href="/contacts/import">"Import .csv"</A>
+                </RequireRole>
+                <CsvExport contacts=results />
+                <RequireRole role=Role::Editor>
+                    <A href="/contacts/duplicates">"Find duplicates"</A>
+                </RequireRole>
+            </div>
+            <div class="list-mode">
+                // ?page= switches the list between scrolling and pages
+                // a bad ?page= counts as paged, so "Show as one list" gets rid of it
+                {move || if page() != Ok(None) {
+                    view! { <A href=with_query_param("page", None)>"Show as one list"</A> }
+                } else {
+                    view! { <A href=with_query_param("page", Some("1".to_string()))>"Show in pages"</A> }
+                }}
+            </div>
             <div class="contact-list-contacts">
-                <A href="alice">"Alice"</A>
-                <A href="bob">"Bob"</A>
-                <A href="steve">"Steve"</A>
+                // what's wrong with a bad :tag or ?page=, in place of the list
+                <ErrorBoundary fallback=|errors| view! { <ErrorList errors/> }>
+                    {move || params_ok().map(|_| view! {
+                    <Show
+                        when=move || results.with(Vec::is_empty)
+                        fallback=move || view! {
+                            {move || match page().ok().flatten() {
+                                Some(page) => view! { <ContactPage results page q /> }.into_view(),
+                                // the links are generated from the repository, so adding a contact there adds it here.
+                                // only the rows in view are mounted, so this stays fast with tens of thousands of contacts
+                                None => view! {
+                                    <VirtualList
+                                        items=results
+                                        // keying on the update time as well means an edited contact gets a fresh row
+                                        key=|contact: &Contact| (contact.id.clone(), contact.updated_at)
+                                        children=move |contact| view! { <ContactListItem contact q /> }
+                                        row_height=ROW_HEIGHT
+                                        height=LIST_HEIGHT
+                                    />
+                                }.into_view(),
+                            }}
+                        }
+                    >
+                        <p class="no-results">
+                            {move || if q().is_empty() {
+                                "No contacts yet.".to_string()
+                            } else {
+                                format!("No contacts match \"{}\".", q())
+                            }}
+                        </p>
+                    </Show>
+                    })}
+                </ErrorBoundary>
             </div>
 
             // <Outlet/> will show the nested child route we can position this
-            // outlet wherever we want within the layout
-            <Outlet/>
+            // outlet wherever we want within the layout. this one fades between contacts
+            <TransitionOutlet animation=OutletAnimation::Fade/>
         </div>
     }
 }
 
+/// One page of the contact list, for when `?page=` is set.
 #[component]
-pub fn ContactInfo() -> impl IntoView {
-    // we can access the :id param reactively with use_params_map
-    let params = use_params_map();
-    let id = move || params.with(|params| params.get("id").cloned().unwrap_or_default());
-
-    // imagine we're loading data from the API here
-    let name = move || match id().as_str() {
-        "alice" => "Alice",
-        "bob" => "Bob",
-        "steve" => "Steve",
-        _ => "User not found."
+fn ContactPage(results: Memo<Vec<Contact>>, page: usize, q: Memo<String>) -> impl IntoView {
+    let len = Signal::derive(move || results.with(Vec::len));
+    let rows = move || results.with(|results| results[page_range(page, results.len())].to_vec());
+
+    view! {
+        <For
+            each=rows
+            key=|contact| (contact.id.clone(), contact.updated_at)
+            children=move |contact| view! {
+                <div style=format!("height: {ROW_HEIGHT}px")><ContactListItem contact q /></div>
+            }
+        />
+        <Pager page len />
+    }
+}
+
+/// One link in the contact list, with the part that matched the search highlighted.
+#[component]
+fn ContactListItem(contact: Contact, q: Memo<String>) -> impl IntoView {
+    let href = keep_query(contact.id.clone());
+    let id = contact.id.clone();
+
+    // the row stays mounted while the search changes, so the highlight is worked out reactively
+    let view_match = move || {
+        let found = best_match(&contact, &q()).filter(|_| !q().is_empty());
+        let (name_indices, other) = match found {
+            Some((MatchedField::Name, found)) => (found.indices, None),
+            Some((MatchedField::Email(text) | MatchedField::Phone(text), found)) => {
+                (vec![], Some((text, found.indices)))
+            }
+            None => (vec![], None),
+        };
+        view! {
+            <Highlight text=contact.name.clone() indices=name_indices />
+            {other.map(|(text, indices)| view! {
+                <small class="matched-field"> " " <Highlight text indices /></small>
+            })}
+        }
     };
 
     view! {
-        <h4>{name}</h4>
-        <div class="contact-info">
-            <div class="tabs">
-                <A href="" exact=true>"Contact Info"</A>
-                <A href="conversations">"Conversations"</A>
-            </div>
+        <A href>
+            {view_match}
+            " "
+            <UnreadCount contact_id=id />
+        </A>
+    }
+}
+
+/// The params of `/contacts/:id` and every tab beneath it.
+#[derive(Params, Clone, PartialEq)]
+pub struct ContactParams {
+    pub id: Id,
+}
+
+/// What `/contacts/:id` loads as soon as it's matched, before <ContactInfo/> renders.
+#[derive(Clone, Copy)]
+pub struct ContactData {
+    pub params: Memo<Result<ContactParams, InvalidParams>>,
+    /// the contact in `:id`, which follows edits to it, and moving to another id
+    pub contact: Memo<Option<Contact>>,
+}
+
+/// The route's loader. The router keeps the route, and with it this data, while
+/// only `:id` changes, so everything in here is reactive.
+pub fn load_contact() -> ContactData {
+    let repository = use_contact_repository();
+    // we can access the :id param reactively, already parsed, with use_typed_params
+    let params = use_typed_params::<ContactParams>();
+    // the repository reads are reactive, so this updates when either the id or the contact changes
+    let contact = create_memo(move |_| params().ok().and_then(|params| repository.get(&params.id.0)));
+    ContactData { params, contact }
+}
+
+#[component]
+pub fn ContactInfo() -> impl IntoView {
+    let ContactData { params, contact } = use_route_data().expect("the route's loader to have run");
+
+    // an id that doesn't parse never gets as far as the layout below, so it's only
+    // a blank there for the moment before the boundary takes over
+    let id = move || params().map(|params| params.id.0).unwrap_or_default();
+    let name = move || contact.with(|contact| contact.as_ref().map(|contact| contact.name.clone()));
+    // only whether the contact exists decides between the layout and the not-found
+    // page, so renaming a contact doesn't re-mount the tab that's open
+    let exists = create_memo(move |_| contact.with(Option::is_some));
+
+    view! {
+        <NotFoundBoundary>
+            {move || {
+                // an id like `a b` isn't even worth looking up
+                params()?;
+                if !exists() {
+                    return Err(NotFound::new("contact", id(), keep_query("/contacts")()).into());
+                }
+                Ok::<_, leptos::error::Error>(view! {
+                    <h4>{name}</h4>
+                    <div class="contact-info">
+                        <div class="tabs">
+                            // the tabs keep the query string, so the search in the list isn't lost
+                            <A href=keep_query("") exact=true>"Contact Info"</A>
+                            <A href=keep_query("conversations")>"Conversations"</A>
+                            <RequireRole role=Role::Editor>
+                                <A href=keep_query("edit")>"Edit"</A>
+                            </RequireRole>
+                        </div>
+                        <ExportContact id=Signal::derive(id) />
+                        // deleting can't be undone, so it's for admins only
+                        <RequireRole role=Role::Admin>
+                            <DeleteContact id=Signal::derive(id) />
+                        </RequireRole>
+
+                        // <outlet> here is the tabs that are neste underneath the /contacts/:id route,
+                        // sliding from one to the next
+                        <TransitionOutlet animation=OutletAnimation::Slide/>
+                    </div>
+                })
+            }}
+        </NotFoundBoundary>
+    }
+}
 
-            // <outlet> here is the tabs that are neste underneath the /contacts/:id route
-            <Outlet/>
+/// The "Contact Info" tab: everything the repository knows about the contact in `:id`.
+#[component]
+pub fn ContactDetails() -> impl IntoView {
+    let repository = use_contact_repository();
+    // <ContactInfo/> already shows what's wrong with a bad id
+    let params = use_typed_params::<ContactParams>();
+    let contact = move || params().ok().and_then(|params| repository.get(&params.id.0));
+
+    view! {
+        <div class="tab">
+            {move || contact().map(|contact| view! {
+                <dl>
+                    <dt>"Emails"</dt>
+                    {contact.emails.into_iter()
+                        .map(|email| view! { <dd><a href=format!("mailto:{email}")>{email.clone()}</a></dd> })
+                        .collect_view()}
+                    <dt>"Phones"</dt>
+                    {contact.phones.into_iter()
+                        .map(|phone| view! { <dd><a href=format!("tel:{phone}")>{phone.clone()}</a></dd> })
+                        .collect_view()}
+                    <dt>"Tags"</dt>
+                    <dd>
+                        {contact.tags.into_iter()
+                            .map(|tag| view! { <A href=tag_href(&tag)>{format!("#{tag} ")}</A> })
+                            .collect_view()}
+                    </dd>
+                    <dt>"Notes"</dt>
+                    <dd>{contact.notes}</dd>
+                    <dt>"Created"</dt>
+                    <dd>{format_timestamp(contact.created_at)}</dd>
+                    <dt>"Updated"</dt>
+                    <dd>{format_timestamp(contact.updated_at)}</dd>
+                </dl>
+            })}
         </div>
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,145 @@
+use gloo_timers::future::TimeoutFuture;
+use leptos::{html::Div, html::Textarea, *};
+use leptos_router::*;
+
+use super::model::{format_timestamp, Direction, Message};
+use super::repository::use_conversation_repository;
+use super::ContactParams;
+use crate::loaders::{use_loader_cache, LoaderCache, SharedLoad};
+use crate::params::use_typed_params;
+use crate::sleep::sleep;
+
+// pretend to hand the message to a server and wait for it to be accepted
+async fn deliver(message: Message) -> Message {
+    TimeoutFuture::new(500).await;
+    message
+}
+
+// pretend to fetch the thread's history from a server. that only happens once per
+// contact: from then on the repository has the thread, and keeps it up to date
+async fn fetch_thread() {
+    sleep(500).await;
+}
+
+/// Starts fetching the thread with `contact_id`, unless it's fetched or being fetched already.
+pub fn load_thread(cache: &LoaderCache, contact_id: &str) -> SharedLoad<()> {
+    cache.load(&format!("thread/{contact_id}"), fetch_thread)
+}
+
+/// The route's loader: fetches the thread with the contact in `:id`, alongside
+/// whatever `/contacts/:id` itself loads.
+pub fn load_conversation() -> Resource<String, ()> {
+    let params = use_typed_params::<ContactParams>();
+    // looked up now, while the route is being set up; the fetcher runs long after
+    let cache = use_loader_cache();
+    create_resource(
+        move || params().map(|params| params.id.0).unwrap_or_default(),
+        move |id| load_thread(&cache, &id),
+    )
+}
+
+/// The "Conversations" tab: the thread with the contact in `:id` and a box to reply.
+#[component]
+pub fn Conversations() -> impl IntoView {
+    let conversations = use_conversation_repository();
+    // <ContactInfo/> already shows what's wrong with a bad id
+    let params = use_typed_params::<ContactParams>();
+    let id = move || params().map(|params| params.id.0).unwrap_or_default();
+    // fetched by the route, or already by hovering the link that led here
+    let thread = use_route_data::<Resource<String, ()>>().expect("the route's loader to have run");
+
+    let messages = {
+        let conversations = conversations.clone();
+        move || conversations.messages(&id())
+    };
+
+    // looking at the thread counts as reading it. this also re-runs when a new
+    // message arrives while the tab is open, so that one never shows up as unread
+    create_effect({
+        let conversations = conversations.clone();
+        move |_| conversations.mark_read(&id())
+    });
+
+    // sending is a mutation, so just like AsyncActionComponent we use an action for it
+    let send = create_action(move |message: &Message| {
+        let conversations = conversations.clone();
+        let message = message.clone();
+        async move {
+            let message = deliver(message).await;
+            conversations.append(message);
+        }
+    });
+    let pending = send.pending();
+
+    // keep the newest message in view whenever the thread changes, and once it has loaded
+    let thread_ref = create_node_ref::<Div>();
+    create_effect({
+        let messages = messages.clone();
+        move |_| {
+            messages();
+            if let Some(thread) = thread_ref.get() {
+                thread.set_scroll_top(thread.scroll_height());
+            }
+        }
+    });
+
+    let compose_ref = create_node_ref::<Textarea>();
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let compose = compose_ref.get().expect("textarea to exist");
+        let body = compose.value();
+        if body.trim().is_empty() {
+            return;
+        }
+        send.dispatch(Message::outgoing(id(), body.trim()));
+        compose.set_value("");
+    };
+
+    view! {
+        <div class="tab conversations">
+            <Suspense fallback=|| view! { <p>"Loading the conversation..."</p> }>
+                {
+                    let messages = messages.clone();
+                    move || thread.get().map(|_| view! {
+                        <div class="thread" node_ref=thread_ref style="max-height: 20em; overflow-y: auto">
+                            <For
+                                each=messages.clone()
+                                key=|message| message.id.clone()
+                                children=|message| view! {
+                                    <div
+                                        class="message"
+                                        class:incoming=message.direction == Direction::Incoming
+                                        class:outgoing=message.direction == Direction::Outgoing
+                                    >
+                                        <p>{message.body}</p>
+                                        <small>{format_timestamp(message.sent_at)}</small>
+                                    </div>
+                                }
+                            />
+                        </div>
+                    })
+                }
+            </Suspense>
+            <form on:submit=on_submit>
+                <textarea node_ref=compose_ref placeholder="Write a message..."></textarea>
+                <button type="submit" disabled=pending>"Send"</button>
+                <span>{move || pending().then_some("Sending...")}</span>
+            </form>
+        </div>
+    }
+}
+
+/// A badge with the number of unread messages from a contact; renders nothing when there are none.
+#[component]
+pub fn UnreadCount(
+    /// whose unread messages to count
+    contact_id: String,
+) -> impl IntoView {
+    let conversations = use_conversation_repository();
+    let count = move || conversations.unread_count(&contact_id);
+
+    move || {
+        let count = count();
+        (count > 0).then(|| view! { <span class="unread-count">{count}</span> })
+    }
+}
@@ -0,0 +1,353 @@
+//! Reading and writing contacts as CSV (RFC 4180, with a choice of delimiter).
+//!
+//! Unlike a vCard, a CSV file doesn't say which column is which, so importing is
+//! two steps: [`parse`] the rows, then turn each one into a contact with a
+//! [`ColumnMapping`] the user picked.
+
+use std::fmt;
+
+use super::model::Contact;
+
+/// What went wrong with one row of a CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvError {
+    /// 1-based row in the file, counting the header if there is one
+    pub row: usize,
+    pub message: String,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: {}", self.row, self.message)
+    }
+}
+
+impl std::error::Error for CsvError {}
+
+/// One successfully split row of a CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvRow {
+    /// 1-based row in the file, counting the header if there is one
+    pub number: usize,
+    pub fields: Vec<String>,
+}
+
+/// Splits a CSV file into rows of fields.
+///
+/// Quoted fields may contain the delimiter, line breaks and `""` for a literal quote.
+/// A row with a stray or unterminated quote becomes an error, and parsing carries
+/// on with the next line.
+pub fn parse(input: &str, delimiter: char) -> Vec<Result<CsvRow, CsvError>> {
+    let mut rows = vec![];
+    let mut chars = input.chars().peekable();
+    let mut row_number = 0;
+
+    while chars.peek().is_some() {
+        row_number += 1;
+        let mut fields = vec![];
+        let mut field = String::new();
+        let mut state = FieldState::Start;
+        let mut error = None;
+
+        loop {
+            let Some(c) = chars.next() else {
+                if state == FieldState::Quoted {
+                    error = Some("unterminated quoted field");
+                }
+                break;
+            };
+            match (state, c) {
+                (FieldState::Quoted, '"') => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        state = FieldState::Closed;
+                    }
+                }
+                (FieldState::Quoted, c) => field.push(c),
+                (_, c) if c == delimiter => {
+                    fields.push(std::mem::take(&mut field));
+                    state = FieldState::Start;
+                }
+                (_, '\r') if chars.peek() == Some(&'\n') => {}
+                (_, '\n') => break,
+                (FieldState::Start, '"') => state = FieldState::Quoted,
+                (FieldState::Unquoted, '"') => {
+                    error.get_or_insert("unexpected quote inside an unquoted field");
+                    field.push(c);
+                }
+                // only the delimiter or the end of the line may follow a closing quote
+                (FieldState::Closed, c) => {
+                    error.get_or_insert("unexpected text after a closing quote");
+                    field.push(c);
+                }
+                (_, c) => {
+                    field.push(c);
+                    state = FieldState::Unquoted;
+                }
+            }
+        }
+        fields.push(field);
+
+        // a completely empty line is just spacing, not a row with one empty field
+        if fields.len() == 1 && fields[0].is_empty() && state == FieldState::Start && error.is_none() {
+            row_number -= 1;
+            continue;
+        }
+        rows.push(match error {
+            Some(message) => Err(CsvError { row: row_number, message: message.to_string() }),
+            None => Ok(CsvRow { number: row_number, fields }),
+        });
+    }
+    rows
+}
+
+// where `parse` is in the field it's reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldState {
+    // nothing read yet, so a quote opens a quoted field
+    Start,
+    Unquoted,
+    // between the quotes
+    Quoted,
+    // after the closing quote, which can't be opened again
+    Closed,
+}
+
+/// Writes rows as CSV, quoting only the fields that need it.
+pub fn write(rows: &[Vec<String>], delimiter: char) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let line = row
+            .iter()
+            .map(|field| {
+                if field.contains([delimiter, '"', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(&delimiter.to_string());
+        out.push_str(&line);
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// The contact field a CSV column is imported into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactField {
+    Ignore,
+    Name,
+    Email,
+    Phone,
+    Notes,
+    Tags,
+}
+
+impl ContactField {
+    pub const ALL: [ContactField; 6] = [
+        ContactField::Ignore,
+        ContactField::Name,
+        ContactField::Email,
+        ContactField::Phone,
+        ContactField::Notes,
+        ContactField::Tags,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ContactField::Ignore => "(ignore)",
+            ContactField::Name => "Name",
+            ContactField::Email => "Email",
+            ContactField::Phone => "Phone",
+            ContactField::Notes => "Notes",
+            ContactField::Tags => "Tags",
+        }
+    }
+
+    /// the inverse of [`ContactField::label`]
+    pub fn from_label(label: &str) -> Self {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.label() == label)
+            .unwrap_or(ContactField::Ignore)
+    }
+
+    /// a guess at the field a header names, e.g. "E-mail Address" is an email
+    pub fn guess(header: &str) -> Self {
+        let header = header.to_lowercase();
+        if header.contains("mail") {
+            ContactField::Email
+        } else if header.contains("phone") || header.contains("tel") || header.contains("mobile") {
+            ContactField::Phone
+        } else if header.contains("note") {
+            ContactField::Notes
+        } else if header.contains("tag") || header.contains("group") || header.contains("categor") {
+            ContactField::Tags
+        } else if header.contains("name") {
+            ContactField::Name
+        } else {
+            ContactField::Ignore
+        }
+    }
+}
+
+/// Which [`ContactField`] each column goes into, by column index.
+///
+/// Several columns may map to the same field: names are joined with a space
+/// (so "First" and "Last" columns both work), notes with a line break, and
+/// every email and phone column adds to the contact's list.
+pub type ColumnMapping = Vec<ContactField>;
+
+/// Builds a contact from one parsed row.
+pub fn to_contact(row: &CsvRow, mapping: &[ContactField]) -> Result<Contact, CsvError> {
+    let mut contact = Contact::new(uuid::Uuid::new_v4().to_string(), "");
+    let mut names = vec![];
+
+    for (field, value) in mapping.iter().zip(&row.fields) {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match field {
+            ContactField::Ignore => {}
+            ContactField::Name => names.push(value),
+            // a single cell may hold several values, which is also how we export them
+            ContactField::Email => contact.emails.extend(split_values(value)),
+            ContactField::Phone => contact.phones.extend(split_values(value)),
+            ContactField::Tags => {
+                for tag in value.split([';', ',', '\n']) {
+                    contact.add_tag(tag);
+                }
+            }
+            ContactField::Notes => {
+                if !contact.notes.is_empty() {
+                    contact.notes.push('\n');
+                }
+                contact.notes.push_str(value);
+            }
+        }
+    }
+
+    if names.is_empty() {
+        return Err(CsvError {
+            row: row.number,
+            message: "no name in the columns mapped to Name".to_string(),
+        });
+    }
+    contact.name = names.join(" ");
+    Ok(contact)
+}
+
+/// The rows [`write`] should produce to export `contacts`, header first.
+pub fn contact_rows(contacts: &[Contact]) -> Vec<Vec<String>> {
+    let header = ["Name", "Email", "Phone", "Notes", "Tags"].map(str::to_string).to_vec();
+    std::iter::once(header)
+        .chain(contacts.iter().map(|contact| {
+            vec![
+                contact.name.clone(),
+                contact.emails.join("; "),
+                contact.phones.join("; "),
+                contact.notes.clone(),
+                contact.tags.join("; "),
+            ]
+        }))
+        .collect()
+}
+
+fn split_values(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split([';', '\n'])
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(input: &str, delimiter: char) -> Vec<Vec<String>> {
+        parse(input, delimiter).into_iter().map(|row| row.unwrap().fields).collect()
+    }
+
+    #[test]
+    fn splits_plain_and_quoted_fields() {
+        assert_eq!(fields("a,b,c\r\n1,,3\n", ','), [vec!["a", "b", "c"], vec!["1", "", "3"]]);
+        assert_eq!(fields("\"a,b\";\"say \"\"hi\"\"\"", ';'), [vec!["a,b", "say \"hi\""]]);
+        assert_eq!(fields("a\tb", '\t'), [vec!["a", "b"]]);
+    }
+
+    #[test]
+    fn quoted_fields_keep_their_line_breaks() {
+        let rows = parse("name,notes\n\"Bob\",\"line one\r\nline two\"\nAlice,x\n", ',');
+        let rows = rows.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+        assert_eq!(rows[1].fields, ["Bob", "line one\r\nline two"]);
+        // numbered by row, not by line
+        assert_eq!((rows[2].number, rows[2].fields.clone()), (3, vec!["Alice".to_string(), "x".to_string()]));
+    }
+
+    #[test]
+    fn a_closed_quote_stays_closed() {
+        assert_eq!(fields("\"\"\"\",x", ','), [vec!["\"", "x"]]);
+        assert_eq!(fields("\"\",x", ','), [vec!["", "x"]]);
+        assert_eq!(
+            parse("\"\"\",x\n", ',')[0],
+            Err(CsvError { row: 1, message: "unterminated quoted field".to_string() })
+        );
+        assert_eq!(
+            parse("\"a\"b,c\n", ',')[0],
+            Err(CsvError { row: 1, message: "unexpected text after a closing quote".to_string() })
+        );
+        assert_eq!(
+            parse("\"\"\"\"x,y\n", ',')[0],
+            Err(CsvError { row: 1, message: "unexpected text after a closing quote".to_string() })
+        );
+    }
+
+    #[test]
+    fn a_broken_row_does_not_stop_the_next() {
+        let rows = parse("a\"b,c\n\nd,e\n", ',');
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].clone().unwrap_err().message, "unexpected quote inside an unquoted field");
+        assert_eq!(rows[1].clone().unwrap(), CsvRow { number: 2, fields: vec!["d".to_string(), "e".to_string()] });
+    }
+
+    #[test]
+    fn write_quotes_only_what_needs_it_and_reads_back() {
+        let rows = vec![
+            vec!["plain".to_string(), "with, comma".to_string(), "with \"quotes\"".to_string()],
+            vec!["two\nlines".to_string(), String::new(), "semi;colon".to_string()],
+        ];
+        let written = write(&rows, ',');
+        assert_eq!(written.lines().next(), Some("plain,\"with, comma\",\"with \"\"quotes\"\"\""));
+        assert_eq!(fields(&written, ','), rows);
+        assert!(write(&rows, ';').contains("\"semi;colon\""));
+    }
+
+    #[test]
+    fn exported_contacts_import_again() {
+        let mut contact = Contact::new("1", "Alice Liddell");
+        contact.emails = vec!["a@example.com".to_string(), "b@example.com".to_string()];
+        contact.notes = "tea, rabbits\nand clocks".to_string();
+        contact.add_tag("friends");
+        contact.add_tag("work");
+        let rows = parse(&write(&contact_rows(&[contact.clone()]), ','), ',');
+        let mapping =
+            [ContactField::Name, ContactField::Email, ContactField::Phone, ContactField::Notes, ContactField::Tags];
+        let imported = to_contact(rows[1].as_ref().unwrap(), &mapping).unwrap();
+        assert_eq!(
+            (imported.name, imported.emails, imported.notes, imported.tags),
+            (contact.name, contact.emails, contact.notes, contact.tags)
+        );
+    }
+
+    #[test]
+    fn a_row_needs_a_name() {
+        let row = CsvRow { number: 4, fields: vec!["  ".to_string(), "a@example.com".to_string()] };
+        assert_eq!(to_contact(&row, &[ContactField::Name, ContactField::Email]).unwrap_err().row, 4);
+    }
+}
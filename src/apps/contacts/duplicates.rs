@@ -0,0 +1,441 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    rc::Rc,
+};
+
+use leptos::*;
+use leptos_router::*;
+use web_sys::FormData;
+
+use super::form::take_field;
+use super::model::Contact;
+use super::repository::{use_contact_repository, use_conversation_repository};
+use crate::auth::{RequireRole, Role};
+use crate::locale::{use_localize, A};
+use crate::not_found::{NotFound, NotFoundBoundary};
+use crate::params::{use_typed_params, Id};
+
+// pairs scoring below this aren't worth showing
+const THRESHOLD: u32 = 40;
+
+/// Two contacts that look like the same person.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicatePair {
+    /// the older of the two, which a merge keeps by default
+    pub a: Contact,
+    pub b: Contact,
+    /// 0 to 100, higher is more likely the same person
+    pub score: u32,
+    /// why they look alike, e.g. "same email bob@example.com"
+    pub reasons: Vec<String>,
+}
+
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// just the digits, so "+1 (555) 0100" and "1-555-0100" are the same number
+pub fn normalize_phone(phone: &str) -> String {
+    phone.chars().filter(char::is_ascii_digit).collect()
+}
+
+// lowercase words, compared as a set so "Smith, John" and "john smith" are the same name
+fn name_words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// one number with and one without the country code still match, as long as
+// enough digits are left that it's not a coincidence
+fn same_phone(a: &str, b: &str) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    short.len() >= 7 && long.ends_with(short)
+}
+
+/// How alike `a` and `b` are, and why, or `None` if they share nothing at all.
+pub fn similarity(a: &Contact, b: &Contact) -> Option<(u32, Vec<String>)> {
+    let mut score = 0;
+    let mut reasons = vec![];
+
+    if let Some(email) = a.emails.iter().find(|email| {
+        b.emails.iter().any(|other| normalize_email(email) == normalize_email(other))
+    }) {
+        score += 60;
+        reasons.push(format!("same email {email}"));
+    }
+
+    if let Some(phone) = a.phones.iter().find(|phone| {
+        b.phones.iter().any(|other| same_phone(&normalize_phone(phone), &normalize_phone(other)))
+    }) {
+        score += 50;
+        reasons.push(format!("same phone {phone}"));
+    }
+
+    let (words_a, words_b) = (
+        name_words(&a.name).into_iter().collect::<BTreeSet<_>>(),
+        name_words(&b.name).into_iter().collect::<BTreeSet<_>>(),
+    );
+    if !words_a.is_empty() && words_a == words_b {
+        score += 40;
+        reasons.push("same name".to_string());
+    } else {
+        // the share of words the two names have in common
+        let shared = words_a.intersection(&words_b).count();
+        let total = words_a.union(&words_b).count();
+        if total > 0 && shared * 2 >= total {
+            score += (30 * shared / total) as u32;
+            reasons.push("similar name".to_string());
+        }
+    }
+
+    (score > 0).then(|| (score.min(100), reasons))
+}
+
+/// Every pair of `contacts` that is likely the same person, most likely first.
+///
+/// Only contacts sharing an email, the end of a phone number or a word of their
+/// name are compared at all, so this doesn't compare every contact with every other.
+pub fn find_duplicates(contacts: &[Contact]) -> Vec<DuplicatePair> {
+    let mut buckets = HashMap::<String, Vec<usize>>::new();
+    for (index, contact) in contacts.iter().enumerate() {
+        let emails = contact.emails.iter().map(|email| format!("email:{}", normalize_email(email)));
+        let phones = contact.phones.iter().filter_map(|phone| {
+            let digits = normalize_phone(phone);
+            (digits.len() >= 7).then(|| format!("phone:{}", &digits[digits.len() - 7..]))
+        });
+        let words = name_words(&contact.name).into_iter().map(|word| format!("name:{word}"));
+        for key in emails.chain(phones).chain(words).collect::<BTreeSet<_>>() {
+            buckets.entry(key).or_default().push(index);
+        }
+    }
+
+    let mut candidates = BTreeSet::new();
+    for indices in buckets.values() {
+        for (position, &first) in indices.iter().enumerate() {
+            for &second in &indices[position + 1..] {
+                candidates.insert((first, second));
+            }
+        }
+    }
+
+    let mut pairs = candidates
+        .into_iter()
+        .filter_map(|(first, second)| {
+            let (mut a, mut b) = (&contacts[first], &contacts[second]);
+            if b.created_at < a.created_at {
+                std::mem::swap(&mut a, &mut b);
+            }
+            let (score, reasons) = similarity(a, b)?;
+            (score >= THRESHOLD).then(|| DuplicatePair { a: a.clone(), b: b.clone(), score, reasons })
+        })
+        .collect::<Vec<_>>();
+    pairs.sort_by_key(|pair| std::cmp::Reverse(pair.score));
+    pairs
+}
+
+/// Which record's value of a field survives a merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pick {
+    A,
+    B,
+    /// both values: the lists combined, or the notes one after another
+    Both,
+}
+
+impl Pick {
+    fn from_value(value: &str) -> Self {
+        match value {
+            "b" => Pick::B,
+            "both" => Pick::Both,
+            _ => Pick::A,
+        }
+    }
+}
+
+/// The [`Pick`] for every field of a merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergePicks {
+    /// a contact only has one name, so [`Pick::Both`] means `a`'s
+    pub name: Pick,
+    pub emails: Pick,
+    pub phones: Pick,
+    pub notes: Pick,
+    pub tags: Pick,
+}
+
+/// Merges `b` into `a`, keeping `a`'s id and the earlier of the two creation times.
+pub fn merge(a: &Contact, b: &Contact, picks: MergePicks) -> Contact {
+    fn pick<T: Clone + PartialEq>(pick: Pick, a: &[T], b: &[T]) -> Vec<T> {
+        match pick {
+            Pick::A => a.to_vec(),
+            Pick::B => b.to_vec(),
+            Pick::Both => {
+                let mut both = a.to_vec();
+                both.extend(b.iter().filter(|value| !a.contains(value)).cloned());
+                both
+            }
+        }
+    }
+
+    let mut merged = a.clone();
+    merged.created_at = a.created_at.min(b.created_at);
+    if picks.name == Pick::B {
+        merged.name = b.name.clone();
+    }
+    merged.emails = pick(picks.emails, &a.emails, &b.emails);
+    merged.phones = pick(picks.phones, &a.phones, &b.phones);
+    merged.notes = match picks.notes {
+        Pick::A => a.notes.clone(),
+        Pick::B => b.notes.clone(),
+        Pick::Both => [a.notes.as_str(), b.notes.as_str()]
+            .into_iter()
+            .filter(|notes| !notes.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    // add_tag keeps the tags sorted
+    merged.tags.clear();
+    for tag in pick(picks.tags, &a.tags, &b.tags) {
+        merged.add_tag(&tag);
+    }
+    merged
+}
+
+/// `/contacts/duplicates`: every likely duplicate, each linking to its merge.
+#[component]
+pub fn DuplicateList() -> impl IntoView {
+    let repository = use_contact_repository();
+    let pairs = create_memo(move |_| find_duplicates(&repository.list()));
+
+    view! {
+        <div class="duplicates">
+            <h4>"Possible duplicates"</h4>
+            <Show
+                when=move || pairs.with(|pairs| !pairs.is_empty())
+                fallback=|| view! { <p>"No duplicates found."</p> }
+            >
+                <ul>
+                    <For
+                        each=pairs
+                        key=|pair| (pair.a.id.clone(), pair.b.id.clone(), pair.a.updated_at, pair.b.updated_at)
+                        children=|pair| view! {
+                            <li>
+                                <A href=format!("/contacts/duplicates/{}/{}", pair.a.id, pair.b.id)>
+                                    {format!("{} and {}", pair.a.name, pair.b.name)}
+                                </A>
+                                {format!(" ({}%: {})", pair.score, pair.reasons.join(", "))}
+                            </li>
+                        }
+                    />
+                </ul>
+            </Show>
+        </div>
+    }
+}
+
+#[derive(Params, Clone, PartialEq)]
+struct MergeParams {
+    // the contact that's kept
+    a: Id,
+    // the one merged into it
+    b: Id,
+}
+
+/// `/contacts/duplicates/:a/:b`: the two contacts side by side, with a choice of
+/// which value to keep for each field.
+///
+/// Like `ContactForm` this is a GET <Form/> that does its work in `on_form_data`:
+/// `:b` is merged into `:a`, its conversation moves over, and we land on the result.
+#[component]
+pub fn MergeContacts() -> impl IntoView {
+    let repository = use_contact_repository();
+    let params = use_typed_params::<MergeParams>();
+
+    let contact = move |Id(id): Id| {
+        repository.get(&id).ok_or_else(|| NotFound::new("contact", id, "/contacts/duplicates"))
+    };
+
+    view! {
+        <NotFoundBoundary>
+            {move || {
+                let MergeParams { a, b } = params()?;
+                let (a, b) = (contact(a)?, contact(b)?);
+                if a.id == b.id {
+                    // merging would delete the only copy
+                    return Ok(view! { <p>"A contact can't be merged with itself."</p> }.into_view());
+                }
+                Ok::<_, leptos::error::Error>(view! { <MergeForm a b /> }.into_view())
+            }}
+        </NotFoundBoundary>
+    }
+}
+
+#[component]
+fn MergeForm(a: Contact, b: Contact) -> impl IntoView {
+    let repository = use_contact_repository();
+    let conversations = use_conversation_repository();
+    let action = use_localize()(&format!("/contacts/{}", a.id));
+    let swap_href = format!("/contacts/duplicates/{}/{}", b.id, a.id);
+
+    let on_form_data = {
+        let (a, b) = (a.clone(), b.clone());
+        Rc::new(move |form_data: &FormData| {
+            let pick = |field| Pick::from_value(&take_field(form_data, field));
+            let picks = MergePicks {
+                name: pick("name"),
+                emails: pick("emails"),
+                phones: pick("phones"),
+                notes: pick("notes"),
+                tags: pick("tags"),
+            };
+            repository.save(merge(&a, &b, picks));
+            conversations.reassign(&b.id, &a.id);
+            repository.delete(&b.id);
+        })
+    };
+
+    // (field, label, a's value, b's value, whether "both" makes sense)
+    let rows = [
+        ("name", "Name", a.name.clone(), b.name.clone(), false),
+        ("emails", "Emails", a.emails.join(", "), b.emails.join(", "), true),
+        ("phones", "Phones", a.phones.join(", "), b.phones.join(", "), true),
+        ("notes", "Notes", a.notes.clone(), b.notes.clone(), true),
+        ("tags", "Tags", a.tags.join(", "), b.tags.join(", "), true),
+    ];
+
+    view! {
+        <div class="merge-contacts">
+            <h4>{format!("Merge {} into {}", b.name, a.name)}</h4>
+            <Form method="GET" action on_form_data>
+                <table>
+                    <tr><th></th><th>{a.name.clone()}</th><th>{b.name.clone()}</th><th>"Both"</th></tr>
+                    {rows.into_iter().map(|(field, label, value_a, value_b, can_keep_both)| {
+                        // lists default to keeping everything, everything else to the record we keep
+                        let default_both = can_keep_both && field != "notes";
+                        view! {
+                            <tr>
+                                <th>{label}</th>
+                                <td><label>
+                                    <input type="radio" name=field value="a" checked=!default_both />
+                                    {value_a}
+                                </label></td>
+                                <td><label>
+                                    <input type="radio" name=field value="b" />
+                                    {value_b}
+                                </label></td>
+                                <td>{can_keep_both.then(|| view! {
+                                    <input type="radio" name=field value="both" checked=default_both />
+                                })}</td>
+                            </tr>
+                        }
+                    }).collect_view()}
+                </table>
+                <p>{format!(
+                    "{}'s record is kept, and {}'s conversation is moved to it.",
+                    a.name, b.name
+                )}</p>
+                // a merge deletes one of the two records
+                <RequireRole role=Role::Admin fallback=|| view! { <p>"Only admins can merge contacts."</p> }>
+                    <input type="submit" value="Merge" />
+                </RequireRole>
+                <A href=swap_href>"Keep the other record instead"</A>
+                <A href="/contacts/duplicates">"Cancel"</A>
+            </Form>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::contacts::model::Timestamp;
+
+    fn contact(id: &str, name: &str, created_at: Timestamp) -> Contact {
+        let mut contact = Contact::new(id, name);
+        contact.created_at = created_at;
+        contact
+    }
+
+    #[test]
+    fn scores_what_two_contacts_share() {
+        let bob = contact("1", "Bob Smith", 1).with_email("Bob@Example.com ").with_phone("+1 (555) 010-0100");
+        let same = contact("2", "smith, bob", 2).with_email("bob@example.com").with_phone("555-010-0100");
+        let (score, reasons) = similarity(&bob, &same).unwrap();
+        assert_eq!(score, 100);
+        assert_eq!(reasons, ["same email Bob@Example.com ", "same phone +1 (555) 010-0100", "same name"]);
+
+        assert_eq!(similarity(&bob, &contact("3", "Bob Smith", 3)), Some((40, vec!["same name".to_string()])));
+        // one of three words: not even similar
+        assert_eq!(similarity(&contact("4", "Bob Lee Smith", 4), &contact("5", "Bob", 5)), None);
+        assert_eq!(
+            similarity(&contact("4", "Bob Lee Smith", 4), &contact("5", "Bob Smith", 5)),
+            Some((20, vec!["similar name".to_string()]))
+        );
+        assert_eq!(similarity(&bob, &contact("6", "Alice", 6)), None);
+    }
+
+    #[test]
+    fn short_numbers_are_not_the_same_phone() {
+        let a = contact("1", "A", 1).with_phone("010 0100");
+        let b = contact("2", "B", 2).with_phone("+44 20 7946 0100");
+        assert_eq!(similarity(&a, &b), None);
+        let c = contact("3", "C", 3).with_phone("0100");
+        let d = contact("4", "D", 4).with_phone("0100");
+        assert_eq!(similarity(&c, &d), None);
+        assert_eq!(similarity(&b, &contact("5", "E", 5).with_phone("20 7946 0100")).map(|(score, _)| score), Some(50));
+    }
+
+    #[test]
+    fn finds_the_likely_pairs_most_likely_first() {
+        let contacts = [
+            contact("newer", "Bob Smith", 20).with_email("bob@example.com"),
+            contact("alice", "Alice", 5),
+            contact("older", "Robert Smith", 10).with_email("BOB@example.com").with_phone("5550100999"),
+            contact("twin", "Alice", 30),
+            contact("phone", "Rob", 40).with_phone("+1 555 0100 999"),
+        ];
+        let pairs = find_duplicates(&contacts)
+            .into_iter()
+            .map(|pair| (pair.a.id, pair.b.id, pair.score))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [
+                // "Bob Smith" and "Robert Smith" share too few words to count, so just the email
+                ("older".to_string(), "newer".to_string(), 60),
+                ("older".to_string(), "phone".to_string(), 50),
+                ("alice".to_string(), "twin".to_string(), 40),
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_the_picked_fields() {
+        let a = contact("a", "Bob", 20).with_email("a@example.com").with_phone("1").with_notes("from a").with_tag("work");
+        let b = contact("b", "Robert", 10)
+            .with_email("b@example.com")
+            .with_email("a@example.com")
+            .with_phone("2")
+            .with_notes("from b")
+            .with_tag("friends")
+            .with_tag("work");
+
+        let picks = MergePicks { name: Pick::B, emails: Pick::Both, phones: Pick::A, notes: Pick::Both, tags: Pick::Both };
+        let merged = merge(&a, &b, picks);
+        assert_eq!((merged.id.as_str(), merged.name.as_str(), merged.created_at), ("a", "Robert", 10));
+        assert_eq!(merged.emails, ["a@example.com", "b@example.com"]);
+        assert_eq!(merged.phones, ["1"]);
+        assert_eq!(merged.notes, "from a\n\nfrom b");
+        assert_eq!(merged.tags, ["friends", "work"]);
+
+        let picks = MergePicks { name: Pick::Both, emails: Pick::B, phones: Pick::B, notes: Pick::A, tags: Pick::A };
+        let merged = merge(&a, &contact("b", "Robert", 30), picks);
+        assert_eq!((merged.name.as_str(), merged.created_at), ("Bob", 20));
+        assert!(merged.emails.is_empty() && merged.phones.is_empty());
+        assert_eq!((merged.notes.as_str(), merged.tags.clone()), ("from a", vec!["work".to_string()]));
+        // notes from only one side aren't padded with blank lines
+        assert_eq!(merge(&a, &contact("c", "C", 1), MergePicks { notes: Pick::Both, ..picks }).notes, "from a");
+    }
+}
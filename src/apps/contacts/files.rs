@@ -0,0 +1,40 @@
+//! Moving text in and out of the browser: reading an `<input type="file">` and
+//! handing the user a file to save. Shared by the vCard and CSV import/export.
+
+use leptos::document;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, File, HtmlAnchorElement, Url};
+
+/// reads a file the user picked as text
+pub async fn read_text(file: File) -> Result<String, String> {
+    JsFuture::from(file.text())
+        .await
+        .map(|text| text.as_string().unwrap_or_default())
+        .map_err(|error| format!("couldn't read {}: {error:?}", file.name()))
+}
+
+/// the files currently selected in an `<input type="file">`
+pub fn selected_files(input: &web_sys::HtmlInputElement) -> Vec<File> {
+    let Some(files) = input.files() else {
+        return vec![];
+    };
+    (0..files.length()).filter_map(|index| files.get(index)).collect()
+}
+
+/// offers `contents` to the user as a download called `filename`
+pub fn download(filename: &str, mime: &str, contents: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let mut options = BlobPropertyBag::new();
+    options.type_(mime);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+
+    // a temporary link with a `download` attribute is the simplest way to save a blob
+    let link = document().create_element("a")?.unchecked_into::<HtmlAnchorElement>();
+    link.set_href(&url);
+    link.set_download(filename);
+    link.click();
+
+    Url::revoke_object_url(&url)
+}
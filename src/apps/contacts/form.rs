@@ -0,0 +1,154 @@
+use std::rc::Rc;
+
+use leptos::*;
+use leptos_router::*;
+use uuid::Uuid;
+use web_sys::FormData;
+
+use super::model::Contact;
+use super::repository::use_contact_repository;
+use super::ContactParams;
+use crate::locale::{use_localize, A};
+use crate::not_found::NotFound;
+use crate::params::use_typed_params;
+
+/// `/contacts/new`: an empty form for a contact that doesn't exist yet.
+#[component]
+pub fn NewContact() -> impl IntoView {
+    // the id is picked up front so the form knows where to navigate once it has saved
+    let contact = Contact::new(Uuid::new_v4().to_string(), "");
+
+    view! {
+        <h4>"New Contact"</h4>
+        <ContactForm contact cancel_href="/contacts".to_string() />
+    }
+}
+
+/// `/contacts/:id/edit`: the same form, filled in with the contact from `:id`.
+#[component]
+pub fn EditContact() -> impl IntoView {
+    let repository = use_contact_repository();
+    // <ContactInfo/> already shows what's wrong with a bad id
+    let params = use_typed_params::<ContactParams>();
+    let id = move || params().map(|params| params.id.0).unwrap_or_default();
+
+    // a missing contact is an error, which the <NotFoundBoundary/> in <ContactInfo/> turns into its page
+    move || match repository.get(&id()) {
+        Some(contact) => {
+            let cancel_href = format!("/contacts/{}", contact.id);
+            Ok(view! { <ContactForm contact cancel_href /> })
+        }
+        None => Err::<_, leptos::error::Error>(NotFound::new("contact", id(), "/contacts").into()),
+    }
+}
+
+/// A form for every editable field of a [`Contact`].
+///
+/// Like the forms in `FormComponent` this is a GET <Form/>, so submitting it is a
+/// client-side navigation. The fields are read (and saved) in `on_form_data`, just
+/// before the router turns them into a query string.
+#[component]
+pub fn ContactForm(
+    /// the contact being edited; its id decides where we land after saving
+    contact: Contact,
+    /// where the "Cancel" link goes
+    cancel_href: String,
+) -> impl IntoView {
+    let repository = use_contact_repository();
+    let action = use_localize()(&format!("/contacts/{}", contact.id));
+
+    let on_form_data = {
+        let contact = contact.clone();
+        Rc::new(move |form_data: &FormData| {
+            let mut contact = contact.clone();
+            contact.name = take_field(form_data, "name").trim().to_string();
+            contact.emails = lines(&take_field(form_data, "emails"));
+            contact.phones = lines(&take_field(form_data, "phones"));
+            contact.notes = take_field(form_data, "notes").trim().to_string();
+            contact.tags.clear();
+            for tag in take_field(form_data, "tags").split(',') {
+                contact.add_tag(tag);
+            }
+            repository.save(contact);
+        })
+    };
+
+    view! {
+        <Form method="GET" action on_form_data class="contact-form">
+            <label>
+                "Name"
+                <input type="text" name="name" required value=contact.name />
+            </label>
+            <label>
+                "Emails (one per line)"
+                <textarea name="emails">{contact.emails.join("\n")}</textarea>
+            </label>
+            <label>
+                "Phones (one per line)"
+                <textarea name="phones">{contact.phones.join("\n")}</textarea>
+            </label>
+            <label>
+                "Tags (comma separated)"
+                <input type="text" name="tags" value=contact.tags.join(", ") />
+            </label>
+            <label>
+                "Notes"
+                <textarea name="notes">{contact.notes}</textarea>
+            </label>
+            <input type="submit" value="Save" />
+            <A href=cancel_href>"Cancel"</A>
+        </Form>
+    }
+}
+
+/// A delete button that asks "are you sure?" before it removes the contact.
+#[component]
+pub fn DeleteContact(
+    /// the id of the contact to delete
+    #[prop(into)]
+    id: Signal<String>,
+) -> impl IntoView {
+    let repository = use_contact_repository();
+    let (confirming, set_confirming) = create_signal(false);
+    let contacts = use_localize()("/contacts");
+
+    let delete = move |_| {
+        repository.delete(&id.get_untracked());
+        set_confirming(false);
+        // the contact is gone, so there is nothing left to show under /contacts/:id
+        use_navigate()(&contacts, Default::default());
+    };
+
+    view! {
+        <div class="delete-contact">
+            <Show
+                when=confirming
+                fallback=move || view! {
+                    <button on:click=move |_| set_confirming(true)>"Delete"</button>
+                }
+            >
+                "Delete this contact? "
+                <button on:click=delete.clone()>"Yes, delete"</button>
+                <button on:click=move |_| set_confirming(false)>"Cancel"</button>
+            </Show>
+        </div>
+    }
+}
+
+/// Removes a field from the form data and returns its value. Taking the fields out
+/// leaves the query string empty, so saving lands on a clean `/contacts/:id` URL.
+pub fn take_field(form_data: &FormData, name: &str) -> String {
+    let value = form_data.get(name).as_string().unwrap_or_default();
+    form_data.delete(name);
+    value
+}
+
+// splits a textarea into its non-empty, trimmed lines
+fn lines(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
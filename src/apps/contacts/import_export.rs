@@ -0,0 +1,352 @@
+use leptos::*;
+use web_sys::{File, HtmlInputElement};
+
+use super::csv::{self, ColumnMapping, ContactField, CsvError, CsvRow};
+use super::files::{download, read_text, selected_files};
+use super::model::Contact;
+use super::repository::use_contact_repository;
+use super::vcard::{self, VCardError, Version};
+use crate::auth::{RequireRole, Role};
+use crate::components::error_handling::ErrorList;
+
+/// What happened to a .vcf file after it was imported.
+#[derive(Debug, Clone, Default)]
+pub struct VCardReport {
+    /// how many cards became contacts
+    pub imported: usize,
+    /// the cards that couldn't be read, and why
+    pub errors: Vec<VCardError>,
+}
+
+/// The vCard toolbar above the contact list: import a .vcf file and export `contacts` as one.
+#[component]
+pub fn VCardTools(
+    /// the contacts "Export .vcf" will save, usually whatever the list is showing
+    #[prop(into)]
+    contacts: Signal<Vec<Contact>>,
+) -> impl IntoView {
+    let repository = use_contact_repository();
+
+    // reading the file is async, so the import is an action, just like AsyncActionComponent's todo
+    let import = create_action(move |file: &File| {
+        let repository = repository.clone();
+        let file = file.clone();
+        async move {
+            let text = read_text(file).await?;
+            let mut report = VCardReport::default();
+            for card in vcard::parse(&text) {
+                match card {
+                    Ok(contact) => {
+                        repository.save(contact);
+                        report.imported += 1;
+                    }
+                    Err(error) => report.errors.push(error),
+                }
+            }
+            Ok::<_, String>(report)
+        }
+    });
+    let pending = import.pending();
+    let report = import.value();
+
+    let on_change = move |ev: ev::Event| {
+        let input = event_target::<HtmlInputElement>(&ev);
+        if let Some(file) = selected_files(&input).into_iter().next() {
+            import.dispatch(file);
+        }
+        // clearing the input lets the same file be picked again
+        input.set_value("");
+    };
+
+    let (version, set_version) = create_signal(Version::V4);
+    let export = move |_| {
+        let text = contacts.with_untracked(|contacts| vcard::write(contacts, version.get_untracked()));
+        if let Err(error) = download("contacts.vcf", "text/vcard", &text) {
+            logging::error!("couldn't export contacts: {error:?}");
+        }
+    };
+
+    view! {
+        <div class="vcard-tools">
+            // importing writes contacts, exporting is for everyone
+            <RequireRole role=Role::Editor>
+                <label>
+                    "Import .vcf "
+                    <input type="file" accept=".vcf,text/vcard" on:change=on_change />
+                </label>
+            </RequireRole>
+            <select on:change=move |ev| set_version(match event_target_value(&ev).as_str() {
+                "3.0" => Version::V3,
+                _ => Version::V4,
+            })>
+                <option value="4.0" selected=move || version() == Version::V4>"vCard 4.0"</option>
+                <option value="3.0" selected=move || version() == Version::V3>"vCard 3.0"</option>
+            </select>
+            <button on:click=export>"Export .vcf"</button>
+            <p>{move || pending().then_some("Importing...")}</p>
+            {move || report().map(|report| match report {
+                Ok(report) => view! { <VCardReportView report /> }.into_view(),
+                Err(error) => view! { <p class="error">{error}</p> }.into_view(),
+            })}
+        </div>
+    }
+}
+
+#[component]
+fn VCardReportView(report: VCardReport) -> impl IntoView {
+    let failed = (!report.errors.is_empty()).then(|| view! {
+        <p>{format!("{} cards couldn't be read:", report.errors.len())}</p>
+        <ul>
+            {report.errors.into_iter()
+                .map(|error| view! { <li>{error.to_string()}</li> })
+                .collect_view()}
+        </ul>
+    });
+
+    view! {
+        <div class="import-report">
+            <p>{format!("Imported {} contacts.", report.imported)}</p>
+            {failed}
+        </div>
+    }
+}
+
+/// Saves a single contact as a .vcf file named after them.
+#[component]
+pub fn ExportContact(
+    /// the id of the contact to export
+    #[prop(into)]
+    id: Signal<String>,
+) -> impl IntoView {
+    let repository = use_contact_repository();
+
+    let export = move |_| {
+        let Some(contact) = repository.get(&id.get_untracked()) else {
+            return;
+        };
+        let filename = format!("{}.vcf", contact.name);
+        if let Err(error) = download(&filename, "text/vcard", &vcard::write(&[contact], Version::V4)) {
+            logging::error!("couldn't export contact: {error:?}");
+        }
+    };
+
+    view! { <button on:click=export>"Export .vcf"</button> }
+}
+
+/// Saves `contacts` as a comma separated .csv file.
+#[component]
+pub fn CsvExport(
+    /// the contacts to export, usually whatever the list is showing
+    #[prop(into)]
+    contacts: Signal<Vec<Contact>>,
+) -> impl IntoView {
+    let export = move |_| {
+        let text = contacts.with_untracked(|contacts| csv::write(&csv::contact_rows(contacts), ','));
+        if let Err(error) = download("contacts.csv", "text/csv", &text) {
+            logging::error!("couldn't export contacts: {error:?}");
+        }
+    };
+
+    view! { <button on:click=export>"Export .csv"</button> }
+}
+
+// how many rows the mapping step previews
+const PREVIEW_ROWS: usize = 5;
+
+/// `/contacts/import`: loads a CSV file, lets the user say which column is which,
+/// previews the result and then imports it.
+#[component]
+pub fn CsvImport() -> impl IntoView {
+    let repository = use_contact_repository();
+
+    let load = create_action(|file: &File| read_text(file.clone()));
+    let loaded = load.value();
+    let text = create_memo(move |_| loaded().and_then(Result::ok));
+
+    let (delimiter, set_delimiter) = create_signal(',');
+    let (has_header, set_has_header) = create_signal(true);
+
+    let rows = create_memo(move |_| {
+        text.with(|text| text.as_deref().map(|text| csv::parse(text, delimiter())).unwrap_or_default())
+    });
+    let header = create_memo(move |_| {
+        if !has_header() {
+            return None;
+        }
+        rows.with(|rows| rows.first().and_then(|row| row.as_ref().ok()).cloned())
+    });
+    // every row after the header, whether it parsed or not
+    let records = create_memo(move |_| {
+        let skip = usize::from(has_header());
+        rows.with(|rows| rows.iter().skip(skip).cloned().collect::<Vec<_>>())
+    });
+    let columns = create_memo(move |_| {
+        rows.with(|rows| rows.iter().flatten().map(|row| row.fields.len()).max().unwrap_or(0))
+    });
+
+    // the user changes the mapping, but a new file, delimiter or header setting
+    // starts it over from a guess based on the header
+    let mapping = create_rw_signal(ColumnMapping::new());
+    create_effect(move |_| {
+        let guessed = match header() {
+            Some(header) => header.fields.iter().map(|name| ContactField::guess(name)).collect(),
+            // without a header, assume the first column is the name
+            None => (0..columns())
+                .map(|column| if column == 0 { ContactField::Name } else { ContactField::Ignore })
+                .collect(),
+        };
+        mapping.set(guessed);
+    });
+
+    let on_change = move |ev: ev::Event| {
+        let input = event_target::<HtmlInputElement>(&ev);
+        if let Some(file) = selected_files(&input).into_iter().next() {
+            load.dispatch(file);
+        }
+    };
+
+    let (imported, set_imported) = create_signal(None::<usize>);
+    let (failed, set_failed) = create_signal(Vec::<CsvError>::new());
+    let import = move |_| {
+        let mut count = 0;
+        let mut errors = vec![];
+        mapping.with_untracked(|mapping| {
+            for record in records.get_untracked() {
+                match record.and_then(|row| csv::to_contact(&row, mapping)) {
+                    Ok(contact) => {
+                        repository.save(contact);
+                        count += 1;
+                    }
+                    Err(error) => errors.push(error),
+                }
+            }
+        });
+        set_imported(Some(count));
+        set_failed(errors);
+    };
+
+    view! {
+        <div class="csv-import">
+            <h4>"Import .csv"</h4>
+            <label>
+                "File "
+                <input type="file" accept=".csv,text/csv" on:change=on_change />
+            </label>
+            {move || loaded().and_then(Result::err).map(|error| view! { <p class="error">{error}</p> })}
+            <label>
+                "Delimiter "
+                <select on:change=move |ev| {
+                    set_delimiter(event_target_value(&ev).chars().next().unwrap_or(','))
+                }>
+                    <option value=",">"Comma"</option>
+                    <option value=";">"Semicolon"</option>
+                    <option value="\t">"Tab"</option>
+                    <option value="|">"Pipe"</option>
+                </select>
+            </label>
+            <label>
+                <input
+                    type="checkbox"
+                    prop:checked=has_header
+                    on:change=move |ev| set_has_header(event_target_checked(&ev))
+                />
+                " First row is a header"
+            </label>
+
+            <Show when=move || text.with(Option::is_some)>
+                <h5>"Columns"</h5>
+                <table class="csv-mapping">
+                    <tr>
+                        {move || (0..columns()).map(|column| {
+                            let name = move || header()
+                                .and_then(|header| header.fields.get(column).cloned())
+                                .unwrap_or_else(|| format!("Column {}", column + 1));
+                            view! { <th>{name}</th> }
+                        }).collect_view()}
+                    </tr>
+                    <tr>
+                        {move || (0..columns()).map(|column| {
+                            let selected = move || mapping.with(|mapping| {
+                                mapping.get(column).copied().unwrap_or(ContactField::Ignore)
+                            });
+                            view! {
+                                <td>
+                                    <select on:change=move |ev| {
+                                        let field = ContactField::from_label(&event_target_value(&ev));
+                                        mapping.update(|mapping| {
+                                            mapping.resize(mapping.len().max(column + 1), ContactField::Ignore);
+                                            mapping[column] = field;
+                                        });
+                                    }>
+                                        {ContactField::ALL.into_iter().map(|field| view! {
+                                            <option selected=move || selected() == field>{field.label()}</option>
+                                        }).collect_view()}
+                                    </select>
+                                </td>
+                            }
+                        }).collect_view()}
+                    </tr>
+                </table>
+
+                <h5>"Preview"</h5>
+                <table class="csv-preview">
+                    <tr><th>"Name"</th><th>"Emails"</th><th>"Phones"</th><th>"Notes"</th><th>"Tags"</th></tr>
+                    {move || records()
+                        .into_iter()
+                        .take(PREVIEW_ROWS)
+                        .map(|record| view! { <CsvPreviewRow record mapping /> })
+                        .collect_view()}
+                </table>
+                <button on:click=import.clone()>
+                    {move || format!("Import {} rows", records.with(Vec::len))}
+                </button>
+            </Show>
+
+            {move || imported().map(|count| view! { <p>{format!("Imported {count} contacts.")}</p> })}
+            // just like ErrorHandling, the boundary collects every Err rendered inside it.
+            // here that is one Err per row that couldn't be imported
+            <ErrorBoundary
+                fallback=|errors| view! {
+                    <p class="error">"These rows couldn't be imported:"</p>
+                    <ErrorList errors/>
+                }
+            >
+                {move || failed().into_iter().map(Err::<(), _>).collect::<Vec<_>>()}
+            </ErrorBoundary>
+        </div>
+    }
+}
+
+/// One previewed row: the contact it would become, or why it can't become one.
+#[component]
+fn CsvPreviewRow(record: Result<CsvRow, CsvError>, mapping: RwSignal<ColumnMapping>) -> impl IntoView {
+    // the row re-renders as the mapping changes; an Err trips this row's own boundary
+    let contact = move || {
+        let record = record.clone();
+        mapping.with(|mapping| record.and_then(|row| csv::to_contact(&row, mapping))).map(|contact| view! {
+            <td>{contact.name}</td>
+            <td>{contact.emails.join(", ")}</td>
+            <td>{contact.phones.join(", ")}</td>
+            <td>{contact.notes}</td>
+            <td>{contact.tags.join(", ")}</td>
+        })
+    };
+
+    view! {
+        <tr>
+            <ErrorBoundary
+                fallback=|errors| view! {
+                    <td colspan="5" class="error">
+                        {move || errors.get()
+                            .into_iter()
+                            .map(|(_, e)| e.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")}
+                    </td>
+                }
+            >
+                {contact}
+            </ErrorBoundary>
+        </tr>
+    }
+}
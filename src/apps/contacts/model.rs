@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+/// milliseconds since the unix epoch, as handed out by the browser's `Date.now()`
+pub type Timestamp = u64;
+
+/// the current time as a [`Timestamp`]: the browser's clock in the browser, and
+/// the system's on the server, where the sample contacts are made too
+pub fn now() -> Timestamp {
+    #[cfg(target_arch = "wasm32")]
+    let now = js_sys::Date::now() as Timestamp;
+    #[cfg(not(target_arch = "wasm32"))]
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as Timestamp);
+    now
+}
+
+/// renders a [`Timestamp`] using the browser's locale. the server doesn't know
+/// the reader's locale, so it writes the time out in UTC, like `2024-03-09 14:05 UTC`
+pub fn format_timestamp(timestamp: Timestamp) -> String {
+    #[cfg(target_arch = "wasm32")]
+    let formatted = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp as f64))
+        .to_locale_string("default", &wasm_bindgen::JsValue::UNDEFINED)
+        .into();
+    #[cfg(not(target_arch = "wasm32"))]
+    let formatted = format_utc(timestamp);
+    formatted
+}
+
+// the calendar date and time of day of a timestamp, in UTC
+#[cfg(not(target_arch = "wasm32"))]
+fn format_utc(timestamp: Timestamp) -> String {
+    let seconds = timestamp / 1000;
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+    // days since the epoch to a date, from Howard Hinnant's `civil_from_days`
+    let days = days as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02} {:02}:{:02} UTC", time / 3600, time % 3600 / 60)
+}
+
+/// A single entry in the address book.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contact {
+    /// stable identifier, also used as the `:id` route segment
+    pub id: String,
+    pub name: String,
+    pub emails: Vec<String>,
+    pub phones: Vec<String>,
+    pub notes: String,
+    /// normalized with [`normalize_tag`], sorted and without duplicates
+    pub tags: Vec<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+impl Contact {
+    /// creates an otherwise empty contact, stamped with the current time
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        let timestamp = now();
+        Self {
+            id: id.into(),
+            name: name.into(),
+            emails: vec![],
+            phones: vec![],
+            notes: String::new(),
+            tags: vec![],
+            created_at: timestamp,
+            updated_at: timestamp,
+        }
+    }
+
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.emails.push(email.into());
+        self
+    }
+
+    pub fn with_phone(mut self, phone: impl Into<String>) -> Self {
+        self.phones.push(phone.into());
+        self
+    }
+
+    pub fn with_notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = notes.into();
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.add_tag(tag);
+        self
+    }
+
+    /// adds a tag, normalizing it and keeping [`Contact::tags`] sorted and unique
+    pub fn add_tag(&mut self, tag: &str) {
+        if let Some(tag) = normalize_tag(tag) {
+            if let Err(index) = self.tags.binary_search(&tag) {
+                self.tags.insert(index, tag);
+            }
+        }
+    }
+}
+
+/// Tags end up in `/contacts/tag/:tag`, so they're kept to lowercase letters, digits,
+/// `-` and `_`, with whitespace turned into `-`. Returns `None` if nothing is left.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_'))
+        .collect::<String>();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Which side of a conversation a [`Message`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// sent by the contact to us
+    Incoming,
+    /// sent by us to the contact
+    Outgoing,
+}
+
+/// One message in the thread we have with a contact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    pub id: String,
+    /// the [`Contact::id`] this message belongs to
+    pub contact_id: String,
+    pub direction: Direction,
+    pub body: String,
+    pub sent_at: Timestamp,
+    /// outgoing messages start out read, incoming ones once the thread has been viewed
+    pub read: bool,
+}
+
+impl Message {
+    /// a message we just wrote to `contact_id`
+    pub fn outgoing(contact_id: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            contact_id: contact_id.into(),
+            direction: Direction::Outgoing,
+            body: body.into(),
+            sent_at: now(),
+            read: true,
+        }
+    }
+
+    /// a message `contact_id` sent us, which we haven't seen yet
+    pub fn incoming(contact_id: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            direction: Direction::Incoming,
+            read: false,
+            ..Self::outgoing(contact_id, body)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_timestamps_in_utc_off_the_browser() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00 UTC");
+        // a leap day, with the seconds dropped
+        assert_eq!(format_utc(1_709_210_059_999), "2024-02-29 12:34 UTC");
+        assert_eq!(format_utc(951_782_400_000), "2000-02-29 00:00 UTC");
+    }
+}
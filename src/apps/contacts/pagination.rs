@@ -0,0 +1,91 @@
+use leptos::*;
+use leptos_router::*;
+
+use super::search::with_query_param;
+use crate::locale::A;
+use crate::params::{use_typed_query, InRange, InvalidParams};
+
+/// how many contacts one page shows
+pub const PAGE_SIZE: usize = 50;
+
+#[derive(Params, Clone, PartialEq)]
+struct PageQuery {
+    // past the last page is fine, it's clamped; this just keeps out nonsense
+    page: Option<InRange<1, 1_000_000>>,
+}
+
+/// The `?page=` from the URL. `None` means the list isn't paged at all.
+pub fn use_page() -> Memo<Result<Option<usize>, InvalidParams>> {
+    let query = use_typed_query::<PageQuery>();
+    create_memo(move |_| query().map(|query| query.page.map(|InRange(page)| page as usize)))
+}
+
+/// how many pages `len` items take up; an empty list still has one (empty) page
+pub fn page_count(len: usize) -> usize {
+    len.div_ceil(PAGE_SIZE).max(1)
+}
+
+/// The item range of `page` (1-based), clamped to the last page.
+pub fn page_range(page: usize, len: usize) -> std::ops::Range<usize> {
+    let page = page.clamp(1, page_count(len));
+    let start = (page - 1) * PAGE_SIZE;
+    start..(start + PAGE_SIZE).min(len)
+}
+
+/// "Previous / Page x of y / Next" links. The links only change `?page=`, so the
+/// search and whatever contact is open stay as they are.
+#[component]
+pub fn Pager(
+    /// the page being shown
+    page: usize,
+    /// how many items there are in total
+    #[prop(into)]
+    len: Signal<usize>,
+) -> impl IntoView {
+    let pages = move || page_count(len());
+    let page = move || page.min(pages());
+
+    view! {
+        <div class="pager">
+            {move || (page() > 1).then(|| view! {
+                <A href=with_query_param("page", Some((page() - 1).to_string()))>"Previous"</A>
+            })}
+            {move || format!(" Page {} of {} ", page(), pages())}
+            {move || (page() < pages()).then(|| view! {
+                <A href=with_query_param("page", Some((page() + 1).to_string()))>"Next"</A>
+            })}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_pages() {
+        assert_eq!(page_count(0), 1);
+        assert_eq!(page_count(1), 1);
+        assert_eq!(page_count(PAGE_SIZE), 1);
+        assert_eq!(page_count(PAGE_SIZE + 1), 2);
+    }
+
+    #[test]
+    fn ranges_of_pages() {
+        assert_eq!(page_range(1, 120), 0..PAGE_SIZE);
+        assert_eq!(page_range(2, 120), PAGE_SIZE..2 * PAGE_SIZE);
+        // the last page is only partly full
+        assert_eq!(page_range(3, 120), 2 * PAGE_SIZE..120);
+        assert_eq!(page_range(2, 2 * PAGE_SIZE), PAGE_SIZE..2 * PAGE_SIZE);
+    }
+
+    #[test]
+    fn out_of_range_pages_are_clamped() {
+        assert_eq!(page_range(0, 120), 0..PAGE_SIZE);
+        assert_eq!(page_range(4, 120), 2 * PAGE_SIZE..120);
+        assert_eq!(page_range(usize::MAX, 120), 2 * PAGE_SIZE..120);
+        // an empty list has its one empty page
+        assert_eq!(page_range(1, 0), 0..0);
+        assert_eq!(page_range(7, 0), 0..0);
+    }
+}
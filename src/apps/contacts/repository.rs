@@ -0,0 +1,346 @@
+use std::rc::Rc;
+
+use leptos::*;
+
+use super::model::{normalize_tag, now, Contact, Direction, Message};
+use super::schema;
+use super::storage::Storage;
+
+/// Where the contact app reads its contacts from.
+///
+/// Implementations are expected to be reactive: reading through the trait inside
+/// a closure in the view should re-run that closure when the contacts change.
+pub trait ContactRepository {
+    /// every contact, sorted by name
+    fn list(&self) -> Vec<Contact>;
+
+    /// a single contact by its id, if it exists
+    fn get(&self, id: &str) -> Option<Contact>;
+
+    /// inserts the contact, or replaces the one with the same id, bumping `updated_at`
+    fn save(&self, contact: Contact);
+
+    /// removes a contact, handing it back if it existed
+    fn delete(&self, id: &str) -> Option<Contact>;
+
+    /// every tag in use, with how many contacts have it, sorted by tag
+    fn tags(&self) -> Vec<(String, usize)> {
+        let mut tags = std::collections::BTreeMap::<String, usize>::new();
+        for contact in self.list() {
+            for tag in contact.tags {
+                *tags.entry(tag).or_default() += 1;
+            }
+        }
+        tags.into_iter().collect()
+    }
+
+    /// renames `from` to `to` on every contact at once. renaming onto a tag that
+    /// already exists merges the two
+    fn rename_tag(&self, from: &str, to: &str);
+
+    /// removes `tag` from every contact at once
+    fn delete_tag(&self, tag: &str);
+}
+
+/// A repository that keeps its contacts in a signal, so every read is tracked just
+/// like reading any other signal would be.
+///
+/// On its own it only lives as long as the page does; see [`Self::stored`] for
+/// one that survives a reload.
+#[derive(Clone, Copy)]
+pub struct InMemoryContactRepository {
+    contacts: RwSignal<Vec<Contact>>,
+}
+
+impl InMemoryContactRepository {
+    pub fn new(contacts: Vec<Contact>) -> Self {
+        Self { contacts: create_rw_signal(contacts) }
+    }
+
+    /// a repository loaded from `storage` (or from `initial()`, the first time),
+    /// which writes every change back to it
+    pub fn stored(storage: Rc<dyn Storage>, initial: impl FnOnce() -> Vec<Contact>) -> Self {
+        let repository = Self::new(schema::CONTACTS.load_or(&*storage, initial));
+        schema::CONTACTS.persist(storage, repository.contacts);
+        repository
+    }
+
+    /// the contacts the tutorial has always shown
+    pub fn sample_data() -> Vec<Contact> {
+        vec![
+            Contact::new("alice", "Alice")
+                .with_email("alice@example.com")
+                .with_phone("+1 555 0100")
+                .with_notes("Met at the Leptos meetup.")
+                .with_tag("friends")
+                .with_tag("meetup"),
+            Contact::new("bob", "Bob")
+                .with_email("bob@example.com")
+                .with_email("bob@work.example.com")
+                .with_phone("+1 555 0101")
+                .with_tag("work"),
+            Contact::new("steve", "Steve")
+                .with_phone("+1 555 0102")
+                .with_phone("+1 555 0103")
+                .with_tag("friends"),
+        ]
+    }
+}
+
+impl ContactRepository for InMemoryContactRepository {
+    fn list(&self) -> Vec<Contact> {
+        let mut contacts = self.contacts.get();
+        contacts.sort_by_cached_key(|contact| contact.name.to_lowercase());
+        contacts
+    }
+
+    fn get(&self, id: &str) -> Option<Contact> {
+        self.contacts
+            .with(|contacts| contacts.iter().find(|contact| contact.id == id).cloned())
+    }
+
+    fn save(&self, mut contact: Contact) {
+        contact.updated_at = now();
+        self.contacts.update(|contacts| {
+            match contacts.iter_mut().find(|existing| existing.id == contact.id) {
+                Some(existing) => *existing = contact,
+                None => contacts.push(contact),
+            }
+        });
+    }
+
+    fn delete(&self, id: &str) -> Option<Contact> {
+        let mut removed = None;
+        self.contacts.update(|contacts| {
+            if let Some(index) = contacts.iter().position(|contact| contact.id == id) {
+                removed = Some(contacts.remove(index));
+            }
+        });
+        removed
+    }
+
+    fn rename_tag(&self, from: &str, to: &str) {
+        let Some(to) = normalize_tag(to) else {
+            return;
+        };
+        let timestamp = now();
+        // a single update, so nothing ever sees some contacts renamed and others not
+        self.contacts.update(|contacts| {
+            for contact in contacts.iter_mut().filter(|contact| contact.tags.iter().any(|tag| tag == from)) {
+                contact.tags.retain(|tag| tag != from);
+                contact.add_tag(&to);
+                contact.updated_at = timestamp;
+            }
+        });
+    }
+
+    fn delete_tag(&self, tag: &str) {
+        let timestamp = now();
+        self.contacts.update(|contacts| {
+            for contact in contacts.iter_mut().filter(|contact| contact.tags.iter().any(|t| t == tag)) {
+                contact.tags.retain(|t| t != tag);
+                contact.updated_at = timestamp;
+            }
+        });
+    }
+}
+
+/// Where the contact app keeps the message threads it has with each contact.
+///
+/// Just like [`ContactRepository`], reads are expected to be reactive.
+pub trait ConversationRepository {
+    /// every message exchanged with `contact_id`, oldest first
+    fn messages(&self, contact_id: &str) -> Vec<Message>;
+
+    /// adds a message to the end of its contact's thread
+    fn append(&self, message: Message);
+
+    /// how many incoming messages from `contact_id` haven't been read yet
+    fn unread_count(&self, contact_id: &str) -> usize;
+
+    /// marks every message from `contact_id` as read
+    fn mark_read(&self, contact_id: &str);
+
+    /// moves every message with `from` into the thread with `to`, e.g. when two
+    /// contacts are merged, keeping the combined thread in the order it was sent
+    fn reassign(&self, from: &str, to: &str);
+}
+
+/// Message threads kept in a signal, which only live as long as the page does
+/// unless they're [`Self::stored`].
+#[derive(Clone, Copy)]
+pub struct InMemoryConversationRepository {
+    messages: RwSignal<Vec<Message>>,
+}
+
+impl InMemoryConversationRepository {
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self { messages: create_rw_signal(messages) }
+    }
+
+    /// threads loaded from `storage` (or from `initial()`, the first time), which
+    /// writes every change back to it
+    pub fn stored(storage: Rc<dyn Storage>, initial: impl FnOnce() -> Vec<Message>) -> Self {
+        let repository = Self::new(schema::MESSAGES.load_or(&*storage, initial));
+        schema::MESSAGES.persist(storage, repository.messages);
+        repository
+    }
+
+    /// a few threads to go with [`InMemoryContactRepository::sample_data`]
+    pub fn sample_data() -> Vec<Message> {
+        let mut read = Message::incoming("alice", "Are you coming to the next meetup?");
+        read.read = true;
+        vec![
+            read,
+            Message::outgoing("alice", "Wouldn't miss it!"),
+            Message::incoming("alice", "Great, see you there."),
+            Message::incoming("bob", "Can you review my PR?"),
+            Message::incoming("bob", "It's the one that adds <Outlet/> to the layout."),
+        ]
+    }
+}
+
+impl ConversationRepository for InMemoryConversationRepository {
+    fn messages(&self, contact_id: &str) -> Vec<Message> {
+        self.messages.with(|messages| {
+            messages
+                .iter()
+                .filter(|message| message.contact_id == contact_id)
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn append(&self, message: Message) {
+        self.messages.update(|messages| messages.push(message));
+    }
+
+    fn unread_count(&self, contact_id: &str) -> usize {
+        self.messages.with(|messages| {
+            messages
+                .iter()
+                .filter(|message| {
+                    message.contact_id == contact_id
+                        && message.direction == Direction::Incoming
+                        && !message.read
+                })
+                .count()
+        })
+    }
+
+    fn mark_read(&self, contact_id: &str) {
+        // only write when something changes: views call this from effects that
+        // also read the messages, and an unconditional update would loop forever
+        if self.unread_count(contact_id) == 0 {
+            return;
+        }
+        self.messages.update(|messages| {
+            for message in messages.iter_mut().filter(|message| message.contact_id == contact_id) {
+                message.read = true;
+            }
+        });
+    }
+
+    fn reassign(&self, from: &str, to: &str) {
+        self.messages.update(|messages| {
+            for message in messages.iter_mut().filter(|message| message.contact_id == from) {
+                message.contact_id = to.to_string();
+            }
+            // stable, so messages sent at the same moment stay in the order they were appended
+            messages.sort_by_key(|message| message.sent_at);
+        });
+    }
+}
+
+// the newtype pattern keeps this from colliding with any other Rc<dyn ...> context
+#[derive(Clone)]
+struct ContactRepositoryContext(Rc<dyn ContactRepository>);
+
+#[derive(Clone)]
+struct ConversationRepositoryContext(Rc<dyn ConversationRepository>);
+
+/// makes `repository` available to every component beneath the caller
+pub fn provide_contact_repository(repository: impl ContactRepository + 'static) {
+    provide_context(ContactRepositoryContext(Rc::new(repository)));
+}
+
+/// the repository provided by the nearest [`provide_contact_repository`]
+pub fn use_contact_repository() -> Rc<dyn ContactRepository> {
+    use_context::<ContactRepositoryContext>()
+        .expect("a contact repository to have been provided")
+        .0
+}
+
+/// makes `repository` available to every component beneath the caller
+pub fn provide_conversation_repository(repository: impl ConversationRepository + 'static) {
+    provide_context(ConversationRepositoryContext(Rc::new(repository)));
+}
+
+/// the repository provided by the nearest [`provide_conversation_repository`]
+pub fn use_conversation_repository() -> Rc<dyn ConversationRepository> {
+    use_context::<ConversationRepositoryContext>()
+        .expect("a conversation repository to have been provided")
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::contacts::storage::MemoryStorage;
+
+    fn ids(contacts: Vec<Contact>) -> Vec<String> {
+        contacts.into_iter().map(|contact| contact.id).collect()
+    }
+
+    #[test]
+    fn stored_contacts_survive_a_reload() {
+        let runtime = create_runtime();
+        let storage = MemoryStorage::new();
+        let repository =
+            InMemoryContactRepository::stored(Rc::new(storage.clone()), InMemoryContactRepository::sample_data);
+        repository.save(Contact::new("carol", "Carol").with_tag("work"));
+        repository.delete("bob");
+        repository.rename_tag("friends", "Family");
+
+        // what the next page load finds, through a clone of the same storage
+        let stored = storage.read(schema::CONTACTS.key).unwrap().unwrap();
+        assert!(stored.starts_with("{\"data\":[") && stored.contains("\"version\":2"), "{stored}");
+        let reloaded = InMemoryContactRepository::stored(Rc::new(storage.clone()), Vec::new);
+        assert_eq!(ids(reloaded.list()), ["alice", "carol", "steve"]);
+        assert_eq!(reloaded.tags(), [("family".to_string(), 2), ("meetup".to_string(), 1), ("work".to_string(), 1)]);
+        runtime.dispose();
+    }
+
+    #[test]
+    fn empty_storage_starts_from_the_initial_contacts() {
+        let runtime = create_runtime();
+        let storage = MemoryStorage::new();
+        let repository =
+            InMemoryContactRepository::stored(Rc::new(storage.clone()), InMemoryContactRepository::sample_data);
+        assert_eq!(ids(repository.list()), ["alice", "bob", "steve"]);
+        // and writes them out straight away
+        assert!(storage.read(schema::CONTACTS.key).unwrap().is_some());
+        runtime.dispose();
+    }
+
+    #[test]
+    fn saving_stamps_the_update_and_keeps_the_creation() {
+        let runtime = create_runtime();
+        let repository = InMemoryContactRepository::stored(Rc::new(MemoryStorage::new()), Vec::new);
+        let contact = Contact::new("carol", "Carol");
+        assert_eq!(contact.created_at, contact.updated_at);
+
+        // as if it had been made long ago
+        repository.save(Contact { created_at: 0, updated_at: 0, ..contact });
+        let saved = repository.get("carol").unwrap();
+        assert_eq!(saved.created_at, 0);
+        assert!(saved.updated_at > saved.created_at);
+
+        // saving it again moves only the update on
+        repository.save(Contact { notes: "met at the meetup".to_string(), ..saved.clone() });
+        let resaved = repository.get("carol").unwrap();
+        assert_eq!(resaved.created_at, 0);
+        assert!(resaved.updated_at >= saved.updated_at);
+        runtime.dispose();
+    }
+}
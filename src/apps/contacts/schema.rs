@@ -0,0 +1,230 @@
+use std::{fmt, rc::Rc};
+
+use leptos::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+use super::storage::{Storage, StorageError};
+
+/// Turns the `data` of a document written by one version into what the next version expects.
+pub type Migration = fn(&mut Value);
+
+/// How one kind of data is laid out in a [`Storage`].
+///
+/// Everything is stored as `{"version": 2, "data": ...}`. A document written by
+/// an older version is brought up to date by running its missing migrations in
+/// order before it's deserialized, so old data keeps loading after an upgrade.
+pub struct Schema {
+    /// the key the document is stored under
+    pub key: &'static str,
+    /// the version this build writes; a document newer than this can't be read
+    pub version: u32,
+    /// `migrations[0]` upgrades version 1 to 2, `migrations[1]` 2 to 3 and so on
+    pub migrations: &'static [Migration],
+}
+
+/// The contacts, as stored by [`super::repository::InMemoryContactRepository::stored`].
+pub static CONTACTS: Schema = Schema {
+    key: "leptos-tutorial.contacts",
+    version: 2,
+    migrations: &[add_tags],
+};
+
+/// The message threads, as stored by [`super::repository::InMemoryConversationRepository::stored`].
+pub static MESSAGES: Schema = Schema {
+    key: "leptos-tutorial.messages",
+    version: 1,
+    migrations: &[],
+};
+
+// version 1 predates tags
+fn add_tags(data: &mut Value) {
+    for contact in data.as_array_mut().into_iter().flatten() {
+        if let Some(contact) = contact.as_object_mut() {
+            contact.entry("tags").or_insert_with(|| json!([]));
+        }
+    }
+}
+
+/// Why a stored document couldn't be read or written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    Storage(StorageError),
+    /// the document isn't the JSON we expect
+    Invalid(String),
+    /// the document was written by a newer version of the app than this one
+    TooNew { found: u32, supported: u32 },
+    /// no version of the app ever wrote this version, like 0
+    UnknownVersion(u32),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Storage(error) => error.fmt(f),
+            SchemaError::Invalid(message) => write!(f, "invalid document: {message}"),
+            SchemaError::TooNew { found, supported } => {
+                write!(f, "document is version {found}, but only up to {supported} is supported")
+            }
+            SchemaError::UnknownVersion(version) => write!(f, "document is version {version}, which doesn't exist"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl From<StorageError> for SchemaError {
+    fn from(error: StorageError) -> Self {
+        Self::Storage(error)
+    }
+}
+
+impl From<serde_json::Error> for SchemaError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Invalid(error.to_string())
+    }
+}
+
+impl Schema {
+    /// reads and migrates the document, or `None` if nothing has been stored yet
+    pub fn load<T: DeserializeOwned>(&self, storage: &dyn Storage) -> Result<Option<T>, SchemaError> {
+        let Some(text) = storage.read(self.key)? else {
+            return Ok(None);
+        };
+        let document = serde_json::from_str::<Value>(&text)?;
+        let version = document
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| SchemaError::Invalid("missing version".to_string()))?;
+        // the migrations a document of `version` is still missing
+        let missing = match u32::try_from(version) {
+            // versions start at 1, so no version of the app has written a 0
+            Ok(0) => return Err(SchemaError::UnknownVersion(0)),
+            Ok(found) if found <= self.version => self
+                .migrations
+                .get(found as usize - 1..)
+                .ok_or(SchemaError::UnknownVersion(found))?,
+            found => {
+                let found = found.unwrap_or(u32::MAX);
+                return Err(SchemaError::TooNew { found, supported: self.version });
+            }
+        };
+        let mut data = document.get("data").cloned().unwrap_or(Value::Null);
+        for migrate in missing {
+            migrate(&mut data);
+        }
+        Ok(Some(serde_json::from_value(data)?))
+    }
+
+    /// writes `data` as the current version
+    pub fn save<T: Serialize>(&self, storage: &dyn Storage, data: &T) -> Result<(), SchemaError> {
+        let document = json!({ "version": self.version, "data": data });
+        storage.write(self.key, &document.to_string())?;
+        Ok(())
+    }
+
+    /// Loads the document, falling back to `initial()` if there isn't one or it
+    /// can't be read. An unreadable document is copied to `{key}.unreadable` first,
+    /// so [`Schema::persist`] doesn't destroy the only copy of it.
+    pub fn load_or<T: DeserializeOwned>(&self, storage: &dyn Storage, initial: impl FnOnce() -> T) -> T {
+        match self.load(storage) {
+            Ok(Some(data)) => data,
+            Ok(None) => initial(),
+            Err(error) => {
+                logging::error!("couldn't load {}: {error}", self.key);
+                if let Ok(Some(text)) = storage.read(self.key) {
+                    let _ = storage.write(&format!("{}.unreadable", self.key), &text);
+                }
+                initial()
+            }
+        }
+    }
+
+    /// writes `signal` to `storage` now and again every time it changes
+    pub fn persist<T: Serialize + 'static>(&'static self, storage: Rc<dyn Storage>, signal: RwSignal<T>) {
+        create_effect(move |_| {
+            signal.with(|data| {
+                if let Err(error) = self.save(&*storage, data) {
+                    logging::error!("couldn't save {}: {error}", self.key);
+                }
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apps::contacts::{model::Contact, storage::MemoryStorage};
+
+    // three versions: 2 added `b` and 3 renamed `a` to `c`, so each older
+    // document needs a different number of the migrations
+    static STEPS: Schema = Schema { key: "steps", version: 3, migrations: &[add_b, rename_a] };
+
+    fn add_b(data: &mut Value) {
+        data["b"] = json!("added");
+    }
+
+    fn rename_a(data: &mut Value) {
+        if let Some(a) = data.as_object_mut().and_then(|data| data.remove("a")) {
+            data["c"] = a;
+        }
+    }
+
+    fn stored(schema: &Schema, document: Value) -> MemoryStorage {
+        let storage = MemoryStorage::new();
+        storage.write(schema.key, &document.to_string()).unwrap();
+        storage
+    }
+
+    fn load(document: Value) -> Result<Option<Value>, SchemaError> {
+        STEPS.load(&stored(&STEPS, document))
+    }
+
+    #[test]
+    fn every_older_version_is_migrated() {
+        assert_eq!(load(json!({ "version": 1, "data": { "a": 1 } })), Ok(Some(json!({ "b": "added", "c": 1 }))));
+        assert_eq!(load(json!({ "version": 2, "data": { "a": 1, "b": 2 } })), Ok(Some(json!({ "b": 2, "c": 1 }))));
+        assert_eq!(load(json!({ "version": 3, "data": { "c": 1 } })), Ok(Some(json!({ "c": 1 }))));
+    }
+
+    #[test]
+    fn unknown_versions_are_errors() {
+        assert_eq!(load(json!({ "version": 0, "data": {} })), Err(SchemaError::UnknownVersion(0)));
+        assert_eq!(load(json!({ "version": 4, "data": {} })), Err(SchemaError::TooNew { found: 4, supported: 3 }));
+        assert_eq!(
+            load(json!({ "version": u64::MAX, "data": {} })),
+            Err(SchemaError::TooNew { found: u32::MAX, supported: 3 })
+        );
+        assert!(matches!(load(json!({ "data": {} })), Err(SchemaError::Invalid(_))));
+        assert!(matches!(load(json!({ "version": "1", "data": {} })), Err(SchemaError::Invalid(_))));
+    }
+
+    #[test]
+    fn nothing_stored_is_none() {
+        assert_eq!(STEPS.load::<Value>(&MemoryStorage::new()), Ok(None));
+    }
+
+    #[test]
+    fn contacts_from_before_tags_load() {
+        let mut contact = serde_json::to_value(Contact::new("alice", "Alice")).unwrap();
+        contact.as_object_mut().unwrap().remove("tags");
+        let storage = stored(&CONTACTS, json!({ "version": 1, "data": [contact] }));
+        let contacts = CONTACTS.load::<Vec<Contact>>(&storage).unwrap().unwrap();
+        assert_eq!((contacts[0].id.as_str(), contacts[0].tags.len()), ("alice", 0));
+    }
+
+    #[test]
+    fn saved_documents_load_again() {
+        let storage = MemoryStorage::new();
+        STEPS.save(&storage, &json!({ "c": 1 })).unwrap();
+        assert_eq!(STEPS.load(&storage), Ok(Some(json!({ "c": 1 }))));
+    }
+
+    #[test]
+    fn an_unreadable_document_is_kept_aside() {
+        let storage = stored(&STEPS, json!({ "version": 9, "data": {} }));
+        assert_eq!(STEPS.load_or(&storage, || json!("initial")), json!("initial"));
+        assert_eq!(storage.read("steps.unreadable").unwrap(), storage.read("steps").unwrap());
+    }
+}
@@ -0,0 +1,252 @@
+use leptos::*;
+use leptos_router::*;
+
+use super::model::Contact;
+use super::pagination::use_page;
+use crate::params::use_typed_query;
+
+/// Where a fuzzy match was found, and which characters matched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    /// higher is better; only meaningful relative to other matches for the same query
+    pub score: i32,
+    /// char (not byte) indices of the matched characters in the text
+    pub indices: Vec<usize>,
+}
+
+/// Matches `query` against `text` as a case-insensitive subsequence, so "alc" finds "Alice".
+///
+/// Contiguous runs and matches at the start of a word score higher, which puts
+/// "bob" above "b-o-b" in the results. Whitespace in the query is ignored.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let original = text.chars().collect::<Vec<_>>();
+    // the text lowercased as a whole, because a char can lowercase to more than one,
+    // like `İ` to `i` and a combining dot. each keeps the index of the char it came from
+    let text = original
+        .iter()
+        .enumerate()
+        .flat_map(|(index, c)| c.to_lowercase().map(move |lower| (lower, index)))
+        .collect::<Vec<_>>();
+
+    let mut indices = Vec::with_capacity(query.len());
+    let mut score = 0;
+    let mut position = 0;
+    let mut last = None;
+    for wanted in query {
+        let found = (position..text.len()).find(|&index| text[index].0 == wanted)?;
+        let index = text[found].1;
+
+        score += 1;
+        if last.is_some_and(|last| last + 1 == found) {
+            // rewards runs of consecutive characters
+            score += 5;
+        }
+        // halfway through a char that lowercased to several isn't the start of anything
+        let starts_char = found == 0 || text[found - 1].1 != index;
+        if starts_char && (index == 0 || !original[index - 1].is_alphanumeric()) {
+            // rewards matches at the start of a word
+            score += 3;
+        }
+        // the further we had to skip, the worse the match
+        score -= (found - position).min(3) as i32;
+
+        if indices.last() != Some(&index) {
+            indices.push(index);
+        }
+        last = Some(found);
+        position = found + 1;
+    }
+
+    Some(FuzzyMatch { score, indices })
+}
+
+/// Which field of a contact a search matched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchedField {
+    Name,
+    Email(String),
+    Phone(String),
+}
+
+/// The best-scoring field of `contact` for `query`, if any field matches at all.
+pub fn best_match(contact: &Contact, query: &str) -> Option<(MatchedField, FuzzyMatch)> {
+    let name = fuzzy_match(query, &contact.name).map(|found| (MatchedField::Name, found));
+    let emails = contact.emails.iter().filter_map(|email| {
+        fuzzy_match(query, email).map(|found| (MatchedField::Email(email.clone()), found))
+    });
+    let phones = contact.phones.iter().filter_map(|phone| {
+        fuzzy_match(query, phone).map(|found| (MatchedField::Phone(phone.clone()), found))
+    });
+
+    // max_by_key returns the last of equal elements, so reverse to let the name win ties
+    name.into_iter()
+        .chain(emails)
+        .chain(phones)
+        .rev()
+        .max_by_key(|(_, found)| found.score)
+}
+
+/// The contacts matching `query`, best match first. An empty query matches everything,
+/// in the order the contacts came in.
+pub fn search(contacts: Vec<Contact>, query: &str) -> Vec<Contact> {
+    if query.trim().is_empty() {
+        return contacts;
+    }
+
+    let mut scored = contacts
+        .into_iter()
+        .filter_map(|contact| best_match(&contact, query).map(|(_, found)| (found.score, contact)))
+        .collect::<Vec<_>>();
+    // sort_by is stable, so equally good matches keep their alphabetical order
+    scored.sort_by(|(a, _), (b, _)| b.cmp(a));
+    scored.into_iter().map(|(_, contact)| contact).collect()
+}
+
+/// Renders `text` with the characters at `indices` wrapped in <mark>.
+#[component]
+pub fn Highlight(
+    /// the text to render
+    text: String,
+    /// char indices to highlight, as returned in [`FuzzyMatch::indices`]
+    #[prop(optional)]
+    indices: Vec<usize>,
+) -> impl IntoView {
+    // group the characters into runs that are either all highlighted or all not
+    let mut runs: Vec<(bool, String)> = vec![];
+    for (index, c) in text.chars().enumerate() {
+        let marked = indices.contains(&index);
+        match runs.last_mut() {
+            Some((run_marked, run)) if *run_marked == marked => run.push(c),
+            _ => runs.push((marked, c.to_string())),
+        }
+    }
+
+    runs.into_iter()
+        .map(|(marked, run)| if marked {
+            view! { <mark>{run}</mark> }.into_view()
+        } else {
+            run.into_view()
+        })
+        .collect_view()
+}
+
+#[derive(Params, Clone, PartialEq)]
+struct SearchQuery {
+    q: Option<String>,
+}
+
+/// The `?q=` search from the URL, or an empty string if there isn't one.
+pub fn use_search_query() -> Memo<String> {
+    // any string is a valid search, so this can't fail
+    let query = use_typed_query::<SearchQuery>();
+    create_memo(move |_| query().ok().and_then(|query| query.q).unwrap_or_default())
+}
+
+/// Appends the current query string to `path`, so links carry the search (and
+/// anything else in the query) along with them.
+pub fn keep_query(path: impl Into<String>) -> impl Fn() -> String + Clone + 'static {
+    let path = path.into();
+    let location = use_location();
+    move || {
+        location.search.with(|search| {
+            if search.is_empty() {
+                path.clone()
+            } else {
+                format!("{path}?{search}")
+            }
+        })
+    }
+}
+
+/// The search box above the contact list.
+///
+/// Like the "Automatic Submission" form in `FormComponent`, this is a GET <Form/>
+/// that submits on every input, so the search lives in `?q=` and can be bookmarked.
+#[component]
+pub fn SearchBox() -> impl IntoView {
+    let q = use_search_query();
+    // submitting to the current path rather than "" keeps an open contact open
+    let location = use_location();
+    let action = move || location.pathname.get();
+    let page = use_page();
+
+    view! {
+        <Form method="GET" action class="contact-search" replace=true noscroll=true>
+            <input
+                type="search"
+                name="q"
+                placeholder="Search name, email or phone"
+                value=q
+                oninput="this.form.requestSubmit()"
+            />
+            // a new search starts over on the first page, but stays in page mode
+            {move || (page() != Ok(None)).then(|| view! { <input type="hidden" name="page" value="1" /> })}
+        </Form>
+    }
+}
+
+/// The current path with the query parameter `key` set to `value` (or removed, for
+/// `None`), keeping every other parameter as it is.
+pub fn with_query_param(key: &'static str, value: Option<String>) -> impl Fn() -> String + Clone + 'static {
+    let location = use_location();
+    move || {
+        let mut query = location.query.get();
+        match value.clone() {
+            Some(value) => query.insert(key.to_string(), value),
+            None => query.remove(key),
+        };
+        format!("{}{}", location.pathname.get(), query.to_query_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_a_subsequence_regardless_of_case() {
+        let found = fuzzy_match("alc", "Alice").unwrap();
+        assert_eq!(found.indices, vec![0, 1, 3]);
+        assert_eq!(fuzzy_match("a l c", "Alice"), Some(found));
+        assert_eq!(fuzzy_match("cla", "Alice"), None);
+        assert_eq!(fuzzy_match("alices", "Alice"), None);
+    }
+
+    #[test]
+    fn ranks_runs_and_word_starts_higher() {
+        let score = |text| fuzzy_match("bob", text).unwrap().score;
+        assert!(score("Bob") > score("B-o-b"));
+        assert!(score("Bob") > score("Jim Bobbins"));
+        assert!(score("Jim Bobbins") > score("jimbobbins"));
+        assert!(score("bo b") > score("b xo xb"));
+    }
+
+    #[test]
+    fn matches_chars_that_lowercase_to_several() {
+        // `İ` lowercases to `i` and a combining dot above
+        assert_eq!(fuzzy_match("i", "İstanbul").unwrap().indices, vec![0]);
+        assert_eq!(fuzzy_match("istanbul", "İSTANBUL").unwrap().indices, (0..8).collect::<Vec<_>>());
+        assert_eq!(fuzzy_match("İz", "İzmir").unwrap().indices, vec![0, 1]);
+    }
+
+    #[test]
+    fn search_puts_the_best_match_first() {
+        let contacts = vec![Contact::new("1", "Jim Bobbins"), Contact::new("2", "Bob"), Contact::new("3", "Alice")];
+        let names = |query| search(contacts.clone(), query).into_iter().map(|c| c.name).collect::<Vec<_>>();
+        assert_eq!(names("bob"), ["Bob", "Jim Bobbins"]);
+        assert_eq!(names("  "), ["Jim Bobbins", "Bob", "Alice"]);
+    }
+
+    #[test]
+    fn best_match_prefers_the_name_on_a_tie() {
+        let mut contact = Contact::new("1", "Bob");
+        contact.emails.push("bob@example.com".to_string());
+        assert_eq!(best_match(&contact, "bob").unwrap().0, MatchedField::Name);
+        assert_eq!(best_match(&contact, "example").unwrap().0, MatchedField::Email("bob@example.com".to_string()));
+    }
+}
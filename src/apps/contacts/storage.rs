@@ -0,0 +1,82 @@
+use std::{cell::RefCell, collections::HashMap, fmt, rc::Rc};
+
+use wasm_bindgen::JsValue;
+
+/// Why reading or writing a [`Storage`] failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageError(pub String);
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "storage error: {}", self.0)
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+impl From<JsValue> for StorageError {
+    fn from(value: JsValue) -> Self {
+        Self(value.as_string().unwrap_or_else(|| format!("{value:?}")))
+    }
+}
+
+/// A place to keep strings by key that outlives the page, or at least pretends to.
+///
+/// This is deliberately as small as localStorage: what goes in the strings (and
+/// which version of it) is up to [`super::schema`].
+pub trait Storage {
+    /// the value stored under `key`, or `None` if nothing is
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError>;
+
+    /// stores `value` under `key`, replacing whatever was there
+    fn write(&self, key: &str, value: &str) -> Result<(), StorageError>;
+}
+
+/// The browser's localStorage, which survives reloads and restarts.
+#[derive(Clone)]
+pub struct LocalStorage(web_sys::Storage);
+
+impl LocalStorage {
+    /// `None` if the browser won't hand out localStorage, e.g. when it's disabled,
+    /// and on the server, where there's no browser at all
+    pub fn new() -> Option<Self> {
+        if leptos::leptos_dom::is_server() {
+            return None;
+        }
+        let storage = leptos::window().local_storage().ok()??;
+        Some(Self(storage))
+    }
+}
+
+impl Storage for LocalStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.0.get_item(key)?)
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        // this is where a full quota shows up
+        Ok(self.0.set_item(key, value)?)
+    }
+}
+
+/// Storage that only lives as long as the page does. Clones share the same
+/// values, so a test can hand one to a repository and look inside another.
+#[derive(Clone, Default)]
+pub struct MemoryStorage(Rc<RefCell<HashMap<String, String>>>);
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, key: &str) -> Result<Option<String>, StorageError> {
+        Ok(self.0.borrow().get(key).cloned())
+    }
+
+    fn write(&self, key: &str, value: &str) -> Result<(), StorageError> {
+        self.0.borrow_mut().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use std::str::FromStr;
+
+use leptos::*;
+use leptos_router::*;
+
+use super::model::normalize_tag;
+use super::repository::use_contact_repository;
+use super::search::keep_query;
+use crate::auth::{RequireRole, Role};
+use crate::locale::{use_localize, A};
+use crate::params::{use_typed_params, InvalidParam, InvalidParams};
+
+/// A tag in the URL, normalized the way [`normalize_tag`] does it, so
+/// `/contacts/tag/Work` lists the contacts tagged `work`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag(pub String);
+
+impl FromStr for Tag {
+    type Err = InvalidParam;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        normalize_tag(value).map(Self).ok_or_else(|| InvalidParam::new(value, "a tag"))
+    }
+}
+
+#[derive(Params, Clone, PartialEq)]
+struct TagParams {
+    // /contacts has no :tag at all
+    tag: Option<Tag>,
+}
+
+/// The `:tag` the list is narrowed to, when under `/contacts/tag/:tag`.
+pub fn use_tag() -> Memo<Result<Option<String>, InvalidParams>> {
+    let params = use_typed_params::<TagParams>();
+    create_memo(move |_| params().map(|params| params.tag.map(|Tag(tag)| tag)))
+}
+
+/// The link to the list narrowed to `tag`, keeping the current search.
+pub fn tag_href(tag: &str) -> impl Fn() -> String + Clone + 'static {
+    keep_query(format!("/contacts/tag/{tag}"))
+}
+
+/// Every tag in use, linking to `/contacts/tag/:tag`, plus a link back to all contacts.
+#[component]
+pub fn TagSidebar() -> impl IntoView {
+    let repository = use_contact_repository();
+    let tag = use_tag();
+
+    view! {
+        <nav class="tag-sidebar">
+            <A href=keep_query("/contacts") exact=true>"All contacts"</A>
+            <For
+                each=move || repository.tags()
+                key=|tag| tag.clone()
+                children=|(tag, count)| view! {
+                    <A href=tag_href(&tag)>{format!("#{tag} ({count})")}</A>
+                }
+            />
+            {move || tag().ok().flatten().map(|tag| view! { <TagManager tag /> })}
+        </nav>
+    }
+}
+
+/// Rename, merge or delete the tag the list is narrowed to.
+///
+/// Each of these is a single repository call, so every contact with the tag
+/// changes at once.
+#[component]
+fn TagManager(tag: String) -> impl IntoView {
+    let repository = use_contact_repository();
+    let (new_name, set_new_name) = create_signal(String::new());
+    let localize = use_localize();
+
+    let rename = {
+        let repository = repository.clone();
+        let tag = tag.clone();
+        let localize = localize.clone();
+        move |ev: ev::SubmitEvent| {
+            ev.prevent_default();
+            let Some(to) = normalize_tag(&new_name.get_untracked()) else {
+                return;
+            };
+            repository.rename_tag(&tag, &to);
+            set_new_name(String::new());
+            // the old tag is gone, so follow the contacts to their new one
+            use_navigate()(&localize(&tag_href(&to)()), Default::default());
+        }
+    };
+
+    let delete = {
+        let tag = tag.clone();
+        move |_| {
+            repository.delete_tag(&tag);
+            use_navigate()(&localize(&keep_query("/contacts")()), Default::default());
+        }
+    };
+
+    // renaming onto a tag that's already in use merges the two
+    let merging = {
+        let repository = use_contact_repository();
+        move || {
+            let to = normalize_tag(&new_name());
+            to.is_some_and(|to| repository.tags().iter().any(|(tag, _)| *tag == to))
+        }
+    };
+
+    view! {
+        <div class="tag-manager">
+            <h5>{format!("#{tag}")}</h5>
+            // a rename rewrites every contact with the tag, so it's for editors
+            <RequireRole role=Role::Editor>
+                <form on:submit=rename.clone()>
+                    <input
+                        type="text"
+                        placeholder="New name"
+                        prop:value=new_name
+                        on:input=move |ev| set_new_name(event_target_value(&ev))
+                    />
+                    <button type="submit">{
+                        let merging = merging.clone();
+                        move || if merging() { "Merge" } else { "Rename" }
+                    }</button>
+                </form>
+            </RequireRole>
+            <RequireRole role=Role::Admin>
+                <button on:click=delete.clone()>"Delete tag"</button>
+            </RequireRole>
+        </div>
+    }
+}
@@ -0,0 +1,443 @@
+//! Reading and writing contacts as vCards (RFC 2426 for 3.0, RFC 6350 for 4.0).
+//!
+//! Only the properties a [`Contact`] has are understood: FN, N, EMAIL, TEL, NOTE,
+//! CATEGORIES (our tags) and UID. Anything else in a card is skipped rather than
+//! rejected, since every address book adds its own extensions.
+
+use std::fmt;
+
+use super::model::Contact;
+use crate::params::Id;
+
+/// The vCard versions we can read and write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Version {
+    V3,
+    V4,
+}
+
+impl Version {
+    fn as_str(self) -> &'static str {
+        match self {
+            Version::V3 => "3.0",
+            Version::V4 => "4.0",
+        }
+    }
+}
+
+/// Why a single card in a .vcf file couldn't be turned into a contact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VCardError {
+    /// 1-based position of the card in the file
+    pub card: usize,
+    /// 1-based line (after unfolding) the problem was found on
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for VCardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "card {} (line {}): {}", self.card, self.line, self.message)
+    }
+}
+
+impl std::error::Error for VCardError {}
+
+/// Parses every card in a .vcf file. Each card is parsed on its own, so one broken
+/// card doesn't stop the others from being imported.
+pub fn parse(input: &str) -> Vec<Result<Contact, VCardError>> {
+    let mut results = vec![];
+    let mut card: Option<CardBuilder> = None;
+
+    for (index, line) in unfold(input).into_iter().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let Some(ContentLine { name, params, value }) = split_line(&line) else {
+            if let Some(builder) = card.as_mut() {
+                builder.error.get_or_insert_with(|| (line_number, format!("malformed line {line:?}")));
+            }
+            continue;
+        };
+
+        match (name.as_str(), card.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VCARD") => {
+                card = Some(CardBuilder::new(results.len() + 1, line_number));
+            }
+            ("BEGIN", Some(builder)) => {
+                // the previous card never ended; report it and start over with this one
+                results.push(Err(builder.fail(line_number, "missing END:VCARD")));
+                card = Some(CardBuilder::new(results.len() + 1, line_number));
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") => {
+                let builder = card.take().expect("card to be open");
+                results.push(builder.finish(line_number));
+            }
+            (_, Some(builder)) => builder.property(line_number, &name, &params, &value),
+            // content outside of BEGIN/END is ignored, like most address books do
+            (_, None) => {}
+        }
+    }
+
+    if let Some(builder) = card {
+        let line = builder.start_line;
+        results.push(Err(builder.fail(line, "missing END:VCARD")));
+    }
+    results
+}
+
+/// Writes contacts as one .vcf file, with CRLF line endings and lines folded at 75 octets.
+pub fn write(contacts: &[Contact], version: Version) -> String {
+    let mut out = String::new();
+    for contact in contacts {
+        let mut lines = vec![
+            "BEGIN:VCARD".to_string(),
+            format!("VERSION:{}", version.as_str()),
+            format!("UID:{}", escape(&contact.id)),
+            format!("FN:{}", escape(&contact.name)),
+            format!("N:{}", structured_name(&contact.name)),
+        ];
+        lines.extend(contact.emails.iter().map(|email| format!("EMAIL:{}", escape(email))));
+        lines.extend(contact.phones.iter().map(|phone| match version {
+            // 4.0 defaults TEL to a URI, so free-form numbers need VALUE=text
+            Version::V4 => format!("TEL;VALUE=text:{}", escape(phone)),
+            Version::V3 => format!("TEL:{}", escape(phone)),
+        }));
+        if !contact.notes.is_empty() {
+            lines.push(format!("NOTE:{}", escape(&contact.notes)));
+        }
+        if !contact.tags.is_empty() {
+            let tags = contact.tags.iter().map(|tag| escape(tag)).collect::<Vec<_>>();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        lines.push("END:VCARD".to_string());
+
+        for line in lines {
+            fold(&line, &mut out);
+        }
+    }
+    out
+}
+
+struct CardBuilder {
+    card: usize,
+    start_line: usize,
+    version: Option<String>,
+    formatted_name: Option<String>,
+    structured_name: Option<String>,
+    uid: Option<String>,
+    contact: Contact,
+    error: Option<(usize, String)>,
+}
+
+impl CardBuilder {
+    fn new(card: usize, start_line: usize) -> Self {
+        Self {
+            card,
+            start_line,
+            version: None,
+            formatted_name: None,
+            structured_name: None,
+            uid: None,
+            contact: Contact::new(uuid::Uuid::new_v4().to_string(), ""),
+            error: None,
+        }
+    }
+
+    fn property(&mut self, line: usize, name: &str, params: &[(String, String)], value: &str) {
+        match name {
+            "VERSION" => {
+                if value != "3.0" && value != "4.0" {
+                    self.error.get_or_insert_with(|| (line, format!("unsupported version {value:?}")));
+                }
+                self.version = Some(value.to_string());
+            }
+            "FN" => self.formatted_name = Some(unescape(value)),
+            "N" => {
+                // Family;Given;Additional;Prefix;Suffix
+                let parts = split_unescaped(value, ';');
+                let get = |index: usize| parts.get(index).map(|part| unescape(part)).unwrap_or_default();
+                let name = [get(3), get(1), get(2), get(0), get(4)]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                self.structured_name = Some(name);
+            }
+            "EMAIL" => self.contact.emails.push(unescape(value)),
+            "TEL" => {
+                let value = unescape(value);
+                // 4.0 numbers are URIs unless VALUE says otherwise, 3.0 ones only if it does
+                let value_type = params.iter().find(|(key, _)| key == "VALUE").map(|(_, value)| value.to_ascii_lowercase());
+                let is_uri = match value_type.as_deref() {
+                    Some(value_type) => value_type == "uri",
+                    None => self.version.as_deref() == Some("4.0"),
+                };
+                let value = match value.strip_prefix("tel:") {
+                    Some(number) if is_uri => number.to_string(),
+                    _ => value,
+                };
+                self.contact.phones.push(value);
+            }
+            "NOTE" => {
+                if !self.contact.notes.is_empty() {
+                    self.contact.notes.push('\n');
+                }
+                self.contact.notes.push_str(&unescape(value));
+            }
+            "CATEGORIES" => {
+                for tag in split_unescaped(value, ',') {
+                    self.contact.add_tag(&unescape(tag));
+                }
+            }
+            "UID" => self.uid = Some(unescape(value)),
+            _ => {}
+        }
+    }
+
+    fn fail(&self, line: usize, message: impl Into<String>) -> VCardError {
+        VCardError { card: self.card, line, message: message.into() }
+    }
+
+    fn finish(mut self, end_line: usize) -> Result<Contact, VCardError> {
+        if let Some((line, message)) = self.error.take() {
+            return Err(self.fail(line, message));
+        }
+        if self.version.is_none() {
+            return Err(self.fail(end_line, "missing VERSION"));
+        }
+
+        let name = self
+            .formatted_name
+            .take()
+            .filter(|name| !name.trim().is_empty())
+            .or(self.structured_name.take())
+            .filter(|name| !name.trim().is_empty());
+        let Some(name) = name else {
+            return Err(self.fail(end_line, "missing FN and N"));
+        };
+        self.contact.name = name.trim().to_string();
+
+        // the uid becomes the route segment, so only keep it if `:id` would accept it
+        let uid = self.uid.as_deref().map(|uid| uid.trim_start_matches("urn:uuid:"));
+        if let Some(Id(uid)) = uid.and_then(|uid| uid.parse().ok()) {
+            self.contact.id = uid;
+        }
+        Ok(self.contact)
+    }
+}
+
+// joins folded lines back together: a line break followed by a space or tab continues the line
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in input.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// one unfolded `group.NAME;KEY=value;...:value` line
+struct ContentLine {
+    // upper-cased, without the group
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+fn split_line(line: &str) -> Option<ContentLine> {
+    // the first colon outside of a quoted parameter value separates the value
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim();
+    // drop the group prefix, e.g. `item1.EMAIL`
+    let name = name.rsplit('.').next()?.to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .map(|param| match param.split_once('=') {
+            Some((key, value)) => (key.to_ascii_uppercase(), value.trim_matches('"').to_string()),
+            // 2.1 style bare parameters, e.g. `TEL;CELL:...`
+            None => ("TYPE".to_string(), param.to_string()),
+        })
+        .collect();
+    Some(ContentLine { name, params, value: value.to_string() })
+}
+
+// splits on `separator`, except where it was escaped with a backslash
+fn split_unescaped(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            c if c == separator && !escaped => {
+                parts.push(&value[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => escaped = false,
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ',' => out.push_str("\\,"),
+            ';' => out.push_str("\\;"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// a best guess at N from a display name: the last word is the family name
+fn structured_name(name: &str) -> String {
+    match name.trim().rsplit_once(' ') {
+        Some((given, family)) => format!("{};{};;;", escape(family), escape(given)),
+        None => format!(";{};;;", escape(name.trim())),
+    }
+}
+
+// appends `line` to `out`, folded so no physical line is longer than 75 octets
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            // the leading space of the continuation counts towards its length
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Contact {
+        let mut contact = Contact::new("alice", "Alice Liddell");
+        contact.emails = vec!["alice@example.com".to_string(), "a.liddell@example.org".to_string()];
+        contact.phones = vec!["+1 555 0100".to_string()];
+        contact.notes = "met at the tea party;\nlikes rabbits, clocks and \\ backslashes".to_string();
+        contact.add_tag("friends");
+        contact.add_tag("work");
+        contact
+    }
+
+    // everything a card carries, which is everything but the timestamps
+    fn fields(contact: &Contact) -> (&str, &str, &[String], &[String], &str, &[String]) {
+        (&contact.id, &contact.name, &contact.emails, &contact.phones, &contact.notes, &contact.tags)
+    }
+
+    #[test]
+    fn round_trips_through_both_versions() {
+        let contact = sample();
+        for version in [Version::V3, Version::V4] {
+            let parsed = parse(&write(std::slice::from_ref(&contact), version));
+            assert_eq!(parsed.len(), 1);
+            let parsed = parsed[0].clone().unwrap();
+            assert_eq!(fields(&parsed), fields(&contact), "vCard {}", version.as_str());
+        }
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        let mut contact = sample();
+        // multi-byte chars, so a fold in the middle of one would show
+        contact.notes = "ünïcödé ".repeat(30);
+        let written = write(&[contact.clone()], Version::V4);
+        assert!(written.split("\r\n").all(|line| line.len() <= 75));
+        assert!(written.contains("\r\n "));
+        assert_eq!(parse(&written)[0].clone().unwrap().notes, contact.notes);
+    }
+
+    #[test]
+    fn unfolds_lines_continued_with_a_space_or_tab() {
+        let card = "BEGIN:VCARD\r\nVERSION:3.0\r\nFN:Bo\r\n b\r\nNOTE:one\n\ttwo\r\nEND:VCARD\r\n";
+        let contact = parse(card)[0].clone().unwrap();
+        assert_eq!(contact.name, "Bob");
+        assert_eq!(contact.notes, "onetwo");
+    }
+
+    #[test]
+    fn falls_back_to_the_structured_name() {
+        let card = "BEGIN:VCARD\nVERSION:4.0\nN:Liddell;Alice;;Dr.;\nEND:VCARD\n";
+        assert_eq!(parse(card)[0].clone().unwrap().name, "Dr. Alice Liddell");
+    }
+
+    #[test]
+    fn reads_tel_uris_by_version() {
+        let card = |version, tel| format!("BEGIN:VCARD\nVERSION:{version}\nFN:X\n{tel}\nEND:VCARD\n");
+        let phones = |card: String| parse(&card)[0].clone().unwrap().phones;
+        assert_eq!(phones(card("4.0", "TEL:tel:+1-555-0100")), ["+1-555-0100"]);
+        assert_eq!(phones(card("4.0", "TEL;VALUE=text:tel:+1")), ["tel:+1"]);
+        assert_eq!(phones(card("3.0", "TEL:tel:+1")), ["tel:+1"]);
+        assert_eq!(phones(card("3.0", "TEL;VALUE=uri:tel:+1")), ["+1"]);
+    }
+
+    #[test]
+    fn a_broken_card_does_not_stop_the_others() {
+        let input = "BEGIN:VCARD\nFN:No Version\nEND:VCARD\n\
+                     BEGIN:VCARD\nVERSION:4.0\nFN:Fine\nEND:VCARD\n\
+                     BEGIN:VCARD\nVERSION:4.0\nFN:Never Ends\n";
+        let results = parse(input);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].clone().unwrap_err().message, "missing VERSION");
+        assert_eq!(results[1].clone().unwrap().name, "Fine");
+        let error = results[2].clone().unwrap_err();
+        // reported where the card began
+        assert_eq!((error.card, error.line, error.message.as_str()), (3, 8, "missing END:VCARD"));
+    }
+
+    #[test]
+    fn keeps_only_uids_that_make_valid_ids() {
+        let card = |uid| format!("BEGIN:VCARD\nVERSION:4.0\nFN:X\nUID:{uid}\nEND:VCARD\n");
+        let id = |uid| parse(&card(uid))[0].clone().unwrap().id;
+        assert_eq!(id("urn:uuid:0b5c1d2e"), "0b5c1d2e");
+        assert_ne!(id("has spaces"), "has spaces");
+    }
+}
@@ -0,0 +1,127 @@
+use std::hash::Hash;
+
+use leptos::{html::Div, *};
+
+/// A scrolling list that only mounts the rows that are actually in view.
+///
+/// Every row is `row_height` pixels tall, so which rows are visible can be worked
+/// out from the scroll position alone. Those rows go through a keyed <For/>, so
+/// scrolling by a row mounts one row and unmounts another instead of re-rendering
+/// the whole window. A tall spacer keeps the scrollbar the size of the full list.
+#[component]
+pub fn VirtualList<T, K, KF, CF, N>(
+    /// every item in the list; only the visible ones are rendered
+    #[prop(into)]
+    items: Signal<Vec<T>>,
+    /// a unique, stable key per item, just like <For/>'s `key`
+    key: KF,
+    /// renders one item, just like <For/>'s `children`
+    children: CF,
+    /// the height of every row, in pixels
+    row_height: f64,
+    /// the height of the scrolling viewport, in pixels
+    height: f64,
+    /// how many extra rows to mount above and below the viewport, so fast scrolling doesn't flash
+    #[prop(default = 5)]
+    overscan: usize,
+) -> impl IntoView
+where
+    T: Clone + 'static,
+    K: Eq + Hash + 'static,
+    KF: Fn(&T) -> K + 'static,
+    CF: Fn(T) -> N + 'static,
+    N: IntoView + 'static,
+{
+    let viewport = create_node_ref::<Div>();
+    let (scroll_top, set_scroll_top) = create_signal(0.0);
+
+    let len = move || items.with(Vec::len);
+    let range = create_memo(move |_| visible_range(scroll_top(), row_height, height, overscan, len()));
+
+    // when the list gets shorter, the browser pulls the viewport back up to its new end
+    // without always saying so with a scroll event, so the position is read back here
+    create_effect(move |_| {
+        let max_top = (len() as f64 * row_height - height).max(0.0);
+        if let Some(viewport) = viewport.get() {
+            if viewport.scroll_top() as f64 > max_top {
+                viewport.set_scroll_top(max_top as i32);
+            }
+            set_scroll_top(viewport.scroll_top() as f64);
+        } else if scroll_top.get_untracked() > max_top {
+            set_scroll_top(max_top);
+        }
+    });
+    let visible = move || {
+        let (start, end) = range();
+        items.with(|items| items[start..end].to_vec())
+    };
+
+    view! {
+        <div
+            class="virtual-list"
+            node_ref=viewport
+            style=format!("height: {height}px; overflow-y: auto")
+            on:scroll=move |_| {
+                if let Some(viewport) = viewport.get() {
+                    set_scroll_top(viewport.scroll_top() as f64);
+                }
+            }
+        >
+            <div style=move || format!("position: relative; height: {}px", len() as f64 * row_height)>
+                <div style=move || format!(
+                    "position: absolute; left: 0; right: 0; top: {}px",
+                    range().0 as f64 * row_height
+                )>
+                    <For
+                        each=visible
+                        key
+                        children=move |item| view! {
+                            <div style=format!("height: {row_height}px; overflow: hidden")>
+                                {children(item)}
+                            </div>
+                        }
+                    />
+                </div>
+            </div>
+        </div>
+    }
+}
+
+// the rows `start..end` to mount for a viewport scrolled `scroll_top` pixels into a list
+// of `len` rows. a position past the end, like the one a list that just got shorter was
+// left at, shows the last rows rather than none at all
+fn visible_range(scroll_top: f64, row_height: f64, height: f64, overscan: usize, len: usize) -> (usize, usize) {
+    let visible = (height / row_height).ceil() as usize;
+    let first = ((scroll_top.max(0.0) / row_height).floor() as usize).min(len.saturating_sub(visible));
+    let start = first.saturating_sub(overscan);
+    let end = (first + visible + overscan).min(len);
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mounts_the_visible_rows_and_the_overscan() {
+        // 10 rows of 20px fit in 200px
+        assert_eq!(visible_range(0.0, 20.0, 200.0, 5, 1000), (0, 15));
+        assert_eq!(visible_range(1000.0, 20.0, 200.0, 5, 1000), (45, 65));
+        // half a row in still counts the row
+        assert_eq!(visible_range(1010.0, 20.0, 200.0, 0, 1000), (50, 60));
+        assert_eq!(visible_range(19_800.0, 20.0, 200.0, 5, 1000), (985, 1000));
+    }
+
+    #[test]
+    fn short_and_empty_lists() {
+        assert_eq!(visible_range(0.0, 20.0, 200.0, 5, 3), (0, 3));
+        assert_eq!(visible_range(0.0, 20.0, 200.0, 5, 0), (0, 0));
+    }
+
+    #[test]
+    fn a_list_that_got_shorter_still_shows_its_end() {
+        // scrolled to row 900, then filtered down to 30 rows
+        assert_eq!(visible_range(18_000.0, 20.0, 200.0, 5, 30), (15, 30));
+        assert_eq!(visible_range(18_000.0, 20.0, 200.0, 0, 4), (0, 4));
+    }
+}
@@ -0,0 +1,355 @@
+use std::{rc::Rc, time::Duration};
+
+use gloo_timers::future::TimeoutFuture;
+use leptos::{html::Input, leptos_dom::is_server, *};
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::apps::contacts::model::now;
+use crate::components::error_handling::ErrorList;
+use crate::locale::{use_canonical, use_localize, A};
+use crate::params::{use_typed_query, LocalPath};
+
+// where the session is kept between reloads
+const STORAGE_KEY: &str = "leptos-tutorial.session";
+// how long a login lasts
+const SESSION_LENGTH: Duration = Duration::from_secs(60 * 60);
+
+/// What a signed in user is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    /// can look at everything
+    Viewer,
+    /// can also create and change things
+    Editor,
+    /// can also delete things
+    Admin,
+}
+
+/// Who is signed in, and until when.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub user: String,
+    pub roles: Vec<Role>,
+    /// milliseconds since the unix epoch, like `Date.now()`
+    pub expires_at: f64,
+}
+
+impl Session {
+    pub fn is_expired(&self) -> bool {
+        now() as f64 >= self.expires_at
+    }
+
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.contains(&role)
+    }
+}
+
+// the pretend user database: (user, password, roles)
+const ACCOUNTS: &[(&str, &str, &[Role])] = &[
+    ("admin", "admin", &[Role::Viewer, Role::Editor, Role::Admin]),
+    ("editor", "editor", &[Role::Viewer, Role::Editor]),
+    ("viewer", "viewer", &[Role::Viewer]),
+];
+
+// pretend to ask a server whether the password is right
+async fn authenticate(user: String, password: String) -> Result<Session, String> {
+    TimeoutFuture::new(300).await;
+    ACCOUNTS
+        .iter()
+        .find(|(name, secret, _)| *name == user && *secret == password)
+        .map(|(name, _, roles)| Session {
+            user: name.to_string(),
+            roles: roles.to_vec(),
+            expires_at: now() as f64 + SESSION_LENGTH.as_millis() as f64,
+        })
+        .ok_or_else(|| "Wrong user name or password.".to_string())
+}
+
+// the newtype keeps this from colliding with any other signal in the context
+#[derive(Clone, Copy)]
+struct SessionContext(RwSignal<Option<Session>>);
+
+// whether the stored session has been read yet. it only lives in localStorage, so
+// a page rendered on the server never knows, and a page that takes over the
+// server's HTML reads it only once it has, so that it starts out the same
+#[derive(Clone, Copy)]
+struct SessionRestored(ReadSignal<bool>);
+
+/// Provides the session to everything beneath the caller, restoring it from
+/// localStorage and saving it back every time someone logs in or out.
+pub fn provide_session() {
+    let storage = if is_server() { None } else { window().local_storage().ok().flatten() };
+    let session = create_rw_signal(None::<Session>);
+    let (restored, set_restored) = create_signal(false);
+
+    let restore = {
+        let storage = storage.clone();
+        move || {
+            let stored = storage
+                .as_ref()
+                .and_then(|storage| storage.get_item(STORAGE_KEY).ok().flatten())
+                .and_then(|json| serde_json::from_str::<Session>(&json).ok())
+                .filter(|session| !session.is_expired());
+            session.set(stored);
+            set_restored(true);
+        }
+    };
+    if cfg!(feature = "hydrate") {
+        // effects only run once the page has been hydrated
+        create_effect(move |_| restore());
+    } else if !is_server() {
+        restore();
+    }
+
+    create_effect(move |_| {
+        let Some(storage) = &storage else {
+            return;
+        };
+        // don't wipe the stored session before it's been read
+        if !restored() {
+            return;
+        }
+        let saved = match session() {
+            Some(session) => serde_json::to_string(&session)
+                .map_err(|error| error.to_string())
+                .and_then(|json| storage.set_item(STORAGE_KEY, &json).map_err(|error| format!("{error:?}"))),
+            None => storage.remove_item(STORAGE_KEY).map_err(|error| format!("{error:?}")),
+        };
+        if let Err(error) = saved {
+            logging::error!("couldn't save the session: {error}");
+        }
+    });
+
+    // log out the moment the session expires, rather than on the next reload
+    create_effect(move |_| {
+        if let Some(expires_at) = session.with(|session| session.as_ref().map(|session| session.expires_at)) {
+            let remaining = (expires_at - now() as f64).max(0.0);
+            set_timeout(
+                move || {
+                    if session.with_untracked(|session| session.as_ref().is_some_and(Session::is_expired)) {
+                        session.set(None);
+                    }
+                },
+                Duration::from_millis(remaining as u64),
+            );
+        }
+    });
+
+    provide_context(SessionContext(session));
+    provide_context(SessionRestored(restored));
+}
+
+/// the current session, or `None` when nobody is signed in
+pub fn use_session() -> RwSignal<Option<Session>> {
+    use_context::<SessionContext>().expect("a session to have been provided").0
+}
+
+/// Whether the stored session has been read yet: never on the server, and only
+/// once the page has been hydrated in the browser. Until then `use_session()` is `None`.
+pub fn use_session_restored() -> ReadSignal<bool> {
+    use_context::<SessionRestored>().expect("a session to have been provided").0
+}
+
+/// whether someone is signed in with `role`
+pub fn use_has_role(role: Role) -> Signal<bool> {
+    let session = use_session();
+    Signal::derive(move || {
+        session.with(|session| session.as_ref().is_some_and(|session| session.has_role(role)))
+    })
+}
+
+/// the current path and query, as a `?next=` for the login page
+fn login_href() -> String {
+    let location = use_location();
+    let localize = use_localize();
+    let pathname = location.pathname.get();
+    if use_canonical()(&pathname) == "/login" {
+        // already there; don't come back to the login page after logging in
+        return localize(&format!("/login{}", location.query.get().to_query_string()));
+    }
+    let here = format!("{pathname}{}", location.query.get().to_query_string());
+    localize(&format!("/login?next={}", encode_component(&here)))
+}
+
+// what encodeURIComponent does, but on the server as well
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+// what a protected route shows
+#[derive(Clone, Copy, PartialEq)]
+enum Access {
+    // the session hasn't been read yet, so there's no telling
+    Unknown,
+    LogIn,
+    Forbidden(Role),
+    Allowed,
+}
+
+/// Wraps the view of `route` so it's only shown to a signed in user with `role`
+/// (or any signed in user, for `None`). Everyone else is redirected to the login
+/// page, which sends them back once they've signed in.
+///
+/// `route` has to be a <Route/>, or a transparent component returning one. Its
+/// nested routes are left alone: the parent's view stands in front of them anyway.
+pub fn protect(route: View, role: Option<Role>) -> View {
+    let definition = route.as_transparent().and_then(|t| t.downcast_ref::<RouteDefinition>());
+    let Some(mut definition) = definition.cloned() else {
+        logging::warn!("protect() needs a <Route/>, so this one was left unprotected");
+        return route;
+    };
+    let view = definition.view.clone();
+    definition.view = Rc::new(move || {
+        let session = use_session();
+        let restored = use_session_restored();
+        // a memo, so the route is only re-rendered when the answer changes, not on every session change
+        let access = create_memo(move |_| {
+            session.with(|session| match (session, role) {
+                _ if !restored() => Access::Unknown,
+                (None, _) => Access::LogIn,
+                (Some(session), Some(role)) if !session.has_role(role) => Access::Forbidden(role),
+                (Some(_), _) => Access::Allowed,
+            })
+        });
+        let view = view.clone();
+        (move || match access() {
+            Access::Unknown => view! { <p>"Checking your session…"</p> }.into_view(),
+            Access::LogIn => view! { <Redirect path=login_href() /> }.into_view(),
+            Access::Forbidden(role) => view! {
+                <p class="error">{format!("You need the {role:?} role to see this page.")}</p>
+            }
+            .into_view(),
+            Access::Allowed => view(),
+        })
+        .into_view()
+    });
+    definition.into_view()
+}
+
+/// A <Route/> only signed in users can see; everyone else is sent to `/login?next=`.
+///
+/// Unlike the router's own `ProtectedRoute` this wraps an existing route, so a
+/// whole tree like `<ProtectedRoute><ContactRoutes/></ProtectedRoute>` can be protected.
+#[component(transparent)]
+pub fn ProtectedRoute(
+    /// the role the user needs, if signing in isn't enough
+    #[prop(optional, into)]
+    role: Option<Role>,
+    /// exactly one <Route/>
+    children: Children,
+) -> impl IntoView {
+    let mut routes = children().as_children().to_vec().into_iter();
+    let route = routes.next().expect("<ProtectedRoute/> to wrap a <Route/>");
+    if routes.next().is_some() {
+        logging::warn!("<ProtectedRoute/> only protects its first child");
+    }
+    protect(route, role)
+}
+
+/// Only shows `children` to users with `role`.
+#[component]
+pub fn RequireRole(
+    role: Role,
+    /// shown instead, if the user doesn't have the role
+    #[prop(optional, into)]
+    fallback: ViewFn,
+    children: ChildrenFn,
+) -> impl IntoView {
+    let allowed = use_has_role(role);
+    view! {
+        <Show when=allowed fallback>
+            {children()}
+        </Show>
+    }
+}
+
+#[derive(Params, Clone, PartialEq)]
+struct LoginQuery {
+    // only paths on this site, so a link can't use the login page to send someone elsewhere
+    next: Option<LocalPath>,
+}
+
+/// `/login`: signs in, then goes to `?next=` (or home).
+#[component]
+pub fn LoginPage() -> impl IntoView {
+    let session = use_session();
+    let query = use_typed_query::<LoginQuery>();
+    // a bad `?next=` is shown below the form, and logging in goes home instead
+    let next = move || {
+        query()
+            .ok()
+            .and_then(|query| query.next)
+            .map_or_else(|| "/".to_string(), |LocalPath(next)| next)
+    };
+    // `next` is where we came from, in whichever locale that was; going home stays in this one
+    let localize = use_localize();
+
+    let user_input = create_node_ref::<Input>();
+    let password_input = create_node_ref::<Input>();
+
+    // logging in is async, so just like AsyncActionComponent it's an action
+    let login = create_action(move |(user, password): &(String, String)| {
+        authenticate(user.clone(), password.clone())
+    });
+    let pending = login.pending();
+
+    // on success, store the session and go back to where we came from
+    create_effect(move |_| {
+        if let Some(Ok(new_session)) = login.value().get() {
+            session.set(Some(new_session));
+            // replace, so "back" doesn't land on the login form again
+            use_navigate()(&localize(&next()), NavigateOptions { replace: true, ..Default::default() });
+        }
+    });
+
+    let on_submit = move |ev: ev::SubmitEvent| {
+        ev.prevent_default();
+        let user = user_input().map(|input| input.value()).unwrap_or_default();
+        let password = password_input().map(|input| input.value()).unwrap_or_default();
+        login.dispatch((user, password));
+    };
+
+    view! {
+        <div class="login">
+            <h3>"Log in"</h3>
+            <form on:submit=on_submit>
+                <label>"User " <input type="text" node_ref=user_input autocomplete="username" required /></label>
+                <label>"Password " <input type="password" node_ref=password_input autocomplete="current-password" required /></label>
+                <input type="submit" value="Log in" disabled=pending />
+            </form>
+            {move || login.value().get().and_then(Result::err).map(|error| view! { <p class="error">{error}</p> })}
+            <ErrorBoundary fallback=|errors| view! { <ErrorList errors/> }>
+                {move || query().map(|_| ())}
+            </ErrorBoundary>
+            <p>"Try admin/admin, editor/editor or viewer/viewer."</p>
+        </div>
+    }
+}
+
+/// Who is signed in, with a link to log in or a button to log out.
+#[component]
+pub fn SessionStatus() -> impl IntoView {
+    let session = use_session();
+    let restored = use_session_restored();
+
+    move || match session() {
+        // neither link nor name until we know which it is
+        _ if !restored() => ().into_view(),
+        Some(current) => view! {
+            <span class="session">
+                {current.user}" "
+                <button on:click=move |_| session.set(None)>"Log out"</button>
+            </span>
+        }.into_view(),
+        None => view! { <A href=login_href>"Log in"</A> }.into_view(),
+    }
+}
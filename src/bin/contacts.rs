@@ -0,0 +1,4 @@
+// the contact app on its own: `trunk serve contacts.html`
+fn main() {
+    leptos_tutorial::apps::mount_standalone("contacts")
+}
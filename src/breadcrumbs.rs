@@ -0,0 +1,165 @@
+use std::{
+    rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::locale::A;
+use crate::params::fill_in;
+
+/// What a route calls itself in the breadcrumb trail.
+///
+/// Either fixed text, or a function that's called in the route's own context (so
+/// it can read params) and returns a signal. A signal that's `None` for now, like
+/// a resource that's still loading, shows as "…" until it has a value.
+#[derive(Clone)]
+pub struct CrumbLabel(Rc<dyn Fn() -> Signal<Option<String>>>);
+
+impl CrumbLabel {
+    pub fn dynamic(label: impl Fn() -> Signal<Option<String>> + 'static) -> Self {
+        Self(Rc::new(label))
+    }
+}
+
+impl From<&'static str> for CrumbLabel {
+    fn from(label: &'static str) -> Self {
+        Self(Rc::new(move || Signal::derive(move || Some(label.to_string()))))
+    }
+}
+
+#[derive(Clone)]
+struct Crumb {
+    id: usize,
+    // how many labelled routes this one is nested in
+    depth: usize,
+    href: Signal<String>,
+    label: Signal<Option<String>>,
+}
+
+// the nearest labelled route above, which a nested route's href builds on
+#[derive(Clone, Copy)]
+struct ParentCrumb {
+    depth: usize,
+    href: Signal<String>,
+}
+
+// the crumbs of every labelled route that is currently rendered
+#[derive(Clone, Copy)]
+struct Trail(RwSignal<Vec<Crumb>>);
+
+/// Makes room for the trail. The routes add their crumbs to it and <Breadcrumbs/>
+/// reads it, so it has to be provided somewhere above both of them.
+pub fn provide_breadcrumbs() {
+    provide_context(Trail(create_rw_signal(vec![])));
+}
+
+/// Gives `route` a crumb in the trail, shown for as long as its view is rendered.
+///
+/// Only the matched routes are rendered, so the trail is exactly the matched route
+/// chain. `route` has to be a <Route/>, or a transparent component returning one.
+pub fn with_breadcrumb(route: View, label: CrumbLabel) -> View {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    let definition = route.as_transparent().and_then(|t| t.downcast_ref::<RouteDefinition>());
+    let Some(mut definition) = definition.cloned() else {
+        logging::warn!("with_breadcrumb() needs a <Route/>, so this one gets no crumb");
+        return route;
+    };
+    let view = definition.view.clone();
+    definition.view = Rc::new(move || {
+        if let Some(Trail(trail)) = use_context::<Trail>() {
+            let route = use_route();
+            // only this route's own segment, like :id
+            let segment = route.original_path().to_string();
+            let params = route.params();
+            let parent = use_context::<ParentCrumb>();
+            let href = Signal::derive(move || {
+                let segment = params.with(|params| fill_in(&segment, params));
+                match parent {
+                    // nested paths are relative to their parent, top-level ones start with /
+                    Some(parent) if !segment.starts_with('/') => format!("{}/{segment}", parent.href.get()),
+                    _ => segment,
+                }
+            });
+            let depth = parent.map_or(0, |parent| parent.depth + 1);
+
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            let crumb = Crumb { id, depth, href, label: (label.0)() };
+            trail.update(|trail| {
+                trail.push(crumb);
+                trail.sort_by_key(|crumb| crumb.depth);
+            });
+            on_cleanup(move || trail.update(|trail| trail.retain(|crumb| crumb.id != id)));
+            // the routes rendered in our <Outlet/> see this as their parent
+            provide_context(ParentCrumb { depth, href });
+        }
+        view()
+    });
+    definition.into_view()
+}
+
+/// A <Route/> with a crumb in the trail.
+///
+/// ```ignore
+/// <BreadcrumbRoute label="Contacts">
+///     <Route path="/contacts" view=ContactList/>
+/// </BreadcrumbRoute>
+/// ```
+#[component(transparent)]
+pub fn BreadcrumbRoute(
+    /// what the route is called in the trail
+    #[prop(into)]
+    label: CrumbLabel,
+    /// exactly one <Route/>
+    children: Children,
+) -> impl IntoView {
+    let mut routes = children().as_children().to_vec().into_iter();
+    let route = routes.next().expect("<BreadcrumbRoute/> to wrap a <Route/>");
+    if routes.next().is_some() {
+        logging::warn!("<BreadcrumbRoute/> only labels its first child");
+    }
+    with_breadcrumb(route, label)
+}
+
+/// What the current page is called: the label of the last crumb in the trail, or
+/// "Home" when there's none.
+pub fn use_page_title() -> Signal<String> {
+    let Trail(trail) = use_context::<Trail>().expect("breadcrumbs to have been provided");
+    Signal::derive(move || {
+        trail.with(|trail| match trail.last() {
+            Some(crumb) => crumb.label.get().unwrap_or_else(|| "…".to_string()),
+            None => "Home".to_string(),
+        })
+    })
+}
+
+/// The trail of labelled routes that are matched right now, from home down to the
+/// current page. Every crumb but the last is a link.
+#[component]
+pub fn Breadcrumbs() -> impl IntoView {
+    let Trail(trail) = use_context::<Trail>().expect("breadcrumbs to have been provided");
+
+    view! {
+        <nav class="breadcrumbs" aria-label="Breadcrumb">
+            <A href="/" exact=true>"Home"</A>
+            <For
+                each=trail
+                key=|crumb| crumb.id
+                children=move |crumb| {
+                    let is_last = move || trail.with(|trail| trail.last().is_some_and(|last| last.id == crumb.id));
+                    let label = move || crumb.label.get().unwrap_or_else(|| "…".to_string());
+                    view! {
+                        " / "
+                        {move || if is_last() {
+                            view! { <span aria-current="page">{label}</span> }.into_view()
+                        } else {
+                            view! { <A href=crumb.href>{label}</A> }.into_view()
+                        }}
+                    }
+                }
+            />
+        </nav>
+    }
+}
@@ -11,6 +11,286 @@ pub mod effects;
 pub mod async_component;
 pub mod suspense_component;
 pub mod transition_component;
+pub mod loader_component;
 pub mod async_action_component;
 pub mod global_state;
-pub mod form_component;
\ No newline at end of file
+pub mod form_component;
+pub mod iteration;
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::breadcrumbs::with_breadcrumb;
+use crate::loaders::{provide_prefetch, RouteLoader};
+use crate::locale::A;
+use crate::not_found::NotFoundPage;
+
+/// One tutorial example, and where it shows up in the sidebar.
+pub struct Example {
+    /// the last segment of its route, `/examples/{slug}`
+    pub slug: &'static str,
+    pub title: &'static str,
+    /// the sidebar heading it's listed under
+    pub category: &'static str,
+    pub view: fn() -> View,
+    /// what its route loads before the view renders, if anything (see loaders.rs)
+    pub loader: Option<RouteLoader>,
+}
+
+/// Every example, in sidebar order. Each one gets its own route, so only the example
+/// you're looking at is mounted.
+pub static EXAMPLES: &[Example] = &[
+    Example {
+        slug: "counter",
+        title: "Counter and progress bars",
+        category: "Basics",
+        view: || app::App().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "control-flow",
+        title: "Control flow",
+        category: "Basics",
+        view: || control_flow::ControlFlow().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "error-handling",
+        title: "Error handling",
+        category: "Basics",
+        view: || error_handling::ErrorHandling().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "values",
+        title: "Rendering values",
+        category: "Lists",
+        view: || iteration::RenderValues().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "static-list",
+        title: "Static list",
+        category: "Lists",
+        view: || view! { <static_list::StaticList length=10 /> }.into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "dynamic-list",
+        title: "Dynamic list",
+        category: "Lists",
+        view: || view! { <dynamic_list::DynamicList initial_length=10 /> }.into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "for",
+        title: "Iterating with <For/>",
+        category: "Lists",
+        view: || iteration::ForExample().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "controlled",
+        title: "Controlled input",
+        category: "Forms",
+        view: || input_controlled::ControlledComponent().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "uncontrolled",
+        title: "Uncontrolled input",
+        category: "Forms",
+        view: || input_uncontrolled::UncontrolledComponent().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "select",
+        title: "Select list",
+        category: "Forms",
+        view: || input_controlled::SelectList().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "router-form",
+        title: "Router <Form/>",
+        category: "Forms",
+        view: || form_component::FormComponent().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "parent-child",
+        title: "Parent to child",
+        category: "Components",
+        view: || parent_child::ParentChild().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "children",
+        title: "Passing children",
+        category: "Components",
+        view: || parent_child::PassChildren().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "effects",
+        title: "create_effect",
+        category: "Effects",
+        view: || effects::Effects().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "manual-effects",
+        title: "Without effects",
+        category: "Effects",
+        view: || view! { <effects::EffectLog><effects::ManualVersion/></effects::EffectLog> }.into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "effect-vs-derived",
+        title: "Effect vs derived signal",
+        category: "Effects",
+        view: || effects::EffectVsDerivedSignal().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "resource",
+        title: "Resources",
+        category: "Async",
+        view: || async_component::AsyncComponent().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "suspense",
+        title: "<Suspense/>",
+        category: "Async",
+        view: || suspense_component::SuspenseComponent().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "transition",
+        title: "<Transition/>",
+        category: "Async",
+        view: || transition_component::TransitionComponent().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "loader",
+        title: "Route loaders",
+        category: "Async",
+        view: || loader_component::LoaderComponent().into_view(),
+        loader: Some(loader_component::LOADER),
+    },
+    Example {
+        slug: "action",
+        title: "Actions",
+        category: "Async",
+        view: || async_action_component::AsyncActionComponent().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "context",
+        title: "Signals in context",
+        category: "Global state",
+        view: || global_state::Option2().into_view(),
+        loader: None,
+    },
+    Example {
+        slug: "global-struct",
+        title: "A global state struct",
+        category: "Global state",
+        view: || global_state::Option3().into_view(),
+        loader: None,
+    },
+];
+
+/// the categories in the order they first appear in [`EXAMPLES`], each with its examples
+pub fn examples_by_category() -> Vec<(&'static str, Vec<&'static Example>)> {
+    let mut categories: Vec<(&'static str, Vec<&'static Example>)> = vec![];
+    for example in EXAMPLES {
+        match categories.iter_mut().find(|(category, _)| *category == example.category) {
+            Some((_, examples)) => examples.push(example),
+            None => categories.push((example.category, vec![example])),
+        }
+    }
+    categories
+}
+
+/// Lets a link to an example start loading before it's clicked, for the examples that load anything.
+pub fn provide_example_prefetches() {
+    for example in EXAMPLES {
+        if let Some(loader) = example.loader {
+            provide_prefetch(&format!("/examples/{}", example.slug), loader.prefetch);
+        }
+    }
+}
+
+/// the route of every example, e.g. for the 404 page's suggestions
+pub fn example_paths() -> Vec<String> {
+    EXAMPLES.iter().map(|example| format!("/examples/{}", example.slug)).collect()
+}
+
+/// `/examples` and one child route per example, generated from [`EXAMPLES`].
+#[component(transparent)]
+pub fn ExampleRoutes() -> impl IntoView {
+    // like <Routes/>, a <Route/> only looks at its direct children, so they're built as a list
+    let children = || {
+        let mut routes = EXAMPLES
+            .iter()
+            .map(|example| {
+                let route = match example.loader {
+                    Some(loader) => view! { <Route path=example.slug view=example.view data=(loader.data)()/> },
+                    None => view! { <Route path=example.slug view=example.view/> },
+                };
+                with_breadcrumb(route, example.title.into())
+            })
+            .collect::<Vec<_>>();
+        routes.push(view! { <Route path="" view=ExampleIndex/> }.into_view());
+        routes.push(view! {
+            <Route path="*any" view=|| {
+                view! { <NotFoundPage paths=example_paths() /> }
+            }/>
+        }.into_view());
+        Fragment::new(routes)
+    };
+
+    with_breadcrumb(
+        view! { <Route path="/examples" view=ExampleLayout children=Box::new(children) /> },
+        "Examples".into(),
+    )
+}
+
+/// The sidebar next to whichever example is open.
+#[component]
+fn ExampleLayout() -> impl IntoView {
+    view! {
+        <div class="examples">
+            <ExampleSidebar/>
+            <div class="example">
+                <Outlet/>
+            </div>
+        </div>
+    }
+}
+
+/// Every example, grouped by category, linking to its route.
+#[component]
+pub fn ExampleSidebar() -> impl IntoView {
+    view! {
+        <nav class="example-sidebar">
+            {examples_by_category().into_iter().map(|(category, examples)| view! {
+                <h4>{category}</h4>
+                <ul>
+                    {examples.into_iter().map(|example| view! {
+                        <li><A href=format!("/examples/{}", example.slug)>{example.title}</A></li>
+                    }).collect_view()}
+                </ul>
+            }).collect_view()}
+        </nav>
+    }
+}
+
+/// `/examples` with no example picked.
+#[component]
+pub fn ExampleIndex() -> impl IntoView {
+    view! { <p>"Pick an example. Each one runs on its own page, so only its effects and resources are active."</p> }
+}
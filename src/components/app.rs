@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 
-use leptos::{create_signal, view, IntoView, Signal, SignalUpdate};
-use crate::{component, components::progress_bar::ProgressBar};
+use leptos::{component, create_signal, view, IntoView, Signal, SignalUpdate};
+use crate::components::progress_bar::ProgressBar;
 
 /// the *[component] macro marks a function as reusable component
 /// Components are the building blocks of you user interface
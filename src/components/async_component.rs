@@ -1,6 +1,7 @@
-use gloo_timers::future::TimeoutFuture;
 use leptos::*;
 
+use crate::sleep::sleep;
+
 #[component]    
 pub fn AsyncComponent() -> impl IntoView {
     // this count is our synchrounous, local state
@@ -68,6 +69,6 @@ pub fn AsyncComponent() -> impl IntoView {
 // here wejust multiply a number by 10 after timer
 pub async fn load_data(value: i32) -> i32 {
     // fake a one-second delay
-    TimeoutFuture::new(1_000).await;
+    sleep(1_000).await;
     value * 10
 }
\ No newline at end of file
@@ -7,6 +7,16 @@ struct LogContext(RwSignal<Vec<String>>);
 
 #[component]
 pub fn Effects() -> impl IntoView {
+    view! {
+        <EffectLog>
+            <CreateAnEffect />
+        </EffectLog>
+    }
+}
+
+/// Shows everything its children `log()`, underneath them.
+#[component]
+pub fn EffectLog(children: Children) -> impl IntoView {
     // just making a visible log here
     // you can ignore this
     let log = create_rw_signal::<Vec<String>>(vec![]);
@@ -17,8 +27,8 @@ pub fn Effects() -> impl IntoView {
     // and makes it easer to refer to it
     provide_context(LogContext(log));
 
-    view! { 
-        <CreateAnEffect />
+    view! {
+        {children()}
         <pre>{logged}</pre>
     }
 }
@@ -75,8 +85,10 @@ fn CreateAnEffect() -> impl IntoView {
     }
 }
 
+/// The same name logger as `CreateAnEffect`, but with event listeners instead of an effect.
+/// It logs through the context, so it has to be inside an <EffectLog/>.
 #[component]
-fn ManualVersion() -> impl IntoView {
+pub fn ManualVersion() -> impl IntoView {
     let first = create_node_ref::<Input>();
     let last = create_node_ref::<Input>();
     let use_last = create_node_ref::<Input>();
@@ -111,8 +123,9 @@ fn ManualVersion() -> impl IntoView {
     }
 }
 
+/// Why a derived signal beats an effect that copies one signal into another.
 #[component]
-fn EffectVsDerivedSignal() -> impl IntoView {
+pub fn EffectVsDerivedSignal() -> impl IntoView {
     let (my_value, set_my_value) = create_signal(String::new());
     // Don't do this
     // let (my_optional_value, set_optional_my_value) = create_signal(Option::<String>::None);
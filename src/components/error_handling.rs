@@ -1,4 +1,4 @@
-use leptos::{component, create_signal, event_target_value, view, ErrorBoundary, IntoView, SignalGet};
+use leptos::*;
 
 #[component]
 pub fn ErrorHandling() -> impl IntoView {
@@ -40,4 +40,18 @@ pub fn ErrorHandling() -> impl IntoView {
             </ErrorBoundary>
         </label>
     }
-}
\ No newline at end of file
+}
+
+/// Every error an <ErrorBoundary/> has caught, one per line. It's made to be the
+/// whole fallback, `fallback=|errors| view! { <ErrorList errors/> }`, or a part of it.
+#[component]
+pub fn ErrorList(errors: RwSignal<Errors>) -> impl IntoView {
+    view! {
+        <ul class="error">
+            {move || errors.get()
+                .into_iter()
+                .map(|(_, e)| view! { <li>{e.to_string()}</li> })
+                .collect_view()}
+        </ul>
+    }
+}
@@ -1,30 +1,83 @@
+use std::str::FromStr;
+
 use leptos::*;
 use leptos_router::*;
 
+use crate::components::error_handling::ErrorList;
+use crate::params::{use_typed_query, InRange, InvalidParam, List};
+
+// the options of the <select>
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Letter {
+    A,
+    B,
+    C,
+}
+
+impl FromStr for Letter {
+    type Err = InvalidParam;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "A" => Ok(Letter::A),
+            "B" => Ok(Letter::B),
+            "C" => Ok(Letter::C),
+            _ => Err(InvalidParam::new(value, "A, B or C")),
+        }
+    }
+}
+
+// the query string, already parsed. every field is optional, since the page
+// starts out without any query at all
+#[derive(Params, Clone, PartialEq)]
+struct FormQuery {
+    name: Option<String>,
+    number: Option<InRange<-100, 100>>,
+    select: Option<Letter>,
+    // comma separated, like ?tags=red,green
+    tags: Option<List<String>>,
+}
+
 #[component]
 pub fn FormComponent() -> impl IntoView {
-    // reactive access to URL query
-    let query = use_query_map();
-    let name = move || query().get("name").cloned().unwrap_or_default();
-    let number = move || query().get("number").cloned().unwrap_or_default();
-    let select = move || query().get("select").cloned().unwrap_or_default();
+    // reactive access to the URL query, parsed into a FormQuery
+    let query = use_typed_query::<FormQuery>();
+    // the inputs start out empty when the query doesn't parse; the table says why
+    let field = move |f: fn(FormQuery) -> Option<String>| move || query().ok().and_then(f).unwrap_or_default();
+    let name = field(|query| query.name);
+    let number = field(|query| query.number.map(|InRange(number)| number.to_string()));
+    let select = move || query().ok().and_then(|query| query.select);
+    let tags = field(|query| query.tags.map(|List(tags)| tags.join(",")));
 
     view! {
-        // read out the URL query strings
-        <table>
-            <tr>
-                <td><code>"name"</code></td>
-                <td>{name}</td>
-            </tr>
-            <tr>
-                <td><code>"number"</code></td>
-                <td>{number}</td>
-            </tr>
-            <tr>
-                <td><code>"select"</code></td>
-                <td>{select}</td>
-            </tr>
-        </table>
+        // read out the URL query strings. a number out of range or a letter that isn't
+        // one of the options is an Err, so the <ErrorBoundary/> shows it instead
+        <ErrorBoundary fallback=|errors| view! { <ErrorList errors/> }>
+            {move || query().map(|query| view! {
+                <table>
+                    <tr>
+                        <td><code>"name"</code></td>
+                        <td>{query.name}</td>
+                    </tr>
+                    <tr>
+                        <td><code>"number"</code></td>
+                        <td>{query.number.map(|InRange(number)| number)}</td>
+                    </tr>
+                    <tr>
+                        <td><code>"select"</code></td>
+                        <td>{query.select.map(|select| format!("{select:?}"))}</td>
+                    </tr>
+                    <tr>
+                        <td><code>"tags"</code></td>
+                        <td>
+                            <ul>
+                                {query.tags.map(|List(tags)| tags.into_iter().map(|tag| view! { <li>{tag}</li> }).collect_view())}
+                            </ul>
+                        </td>
+                    </tr>
+                </table>
+            })}
+        </ErrorBoundary>
         // <Form/> will navigate whenever submitted
         <h2>"Manual Submission"</h2>
         <Form method="GET" action="">
@@ -33,10 +86,11 @@ pub fn FormComponent() -> impl IntoView {
             <input type="number" name="number" value=number/>
             <select name="select">
                 // `selected` will set which starts as selected
-                <option selected=move || select() == "A">"A"</option>
-                <option selected=move || select() == "B">"B"</option>
-                <option selected=move || select() == "C">"C"</option>
+                <option selected=move || select() == Some(Letter::A)>"A"</option>
+                <option selected=move || select() == Some(Letter::B)>"B"</option>
+                <option selected=move || select() == Some(Letter::C)>"C"</option>
             </select>
+            <input type="text" name="tags" placeholder="tags, comma separated" value=tags/>
             // submitting should cause a client-side
             // navigation, not a full reload
             <input type="submit"/>
@@ -49,12 +103,13 @@ pub fn FormComponent() -> impl IntoView {
             <input type="text" name="name" value=name oninput="this.form.requestSubmit()" />
             <input type="number" name="number" value=number oninput="this.form.requestSubmit()" />
             <select name="select" onchange="this.form.requestSubmit()">
-                <option selected=move || select() == "A">"A"</option>
-                <option selected=move || select() == "B">"B"</option>
-                <option selected=move || select() == "C">"C"</option>
+                <option selected=move || select() == Some(Letter::A)>"A"</option>
+                <option selected=move || select() == Some(Letter::B)>"B"</option>
+                <option selected=move || select() == Some(Letter::C)>"C"</option>
             </select>
+            <input type="text" name="tags" placeholder="tags, comma separated" value=tags oninput="this.form.requestSubmit()" />
             // submitting should cause a client-side navigation, not a full reload
             <input type="submit"/>
-        </Form>      
+        </Form>
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,74 @@
+use leptos::*;
+
+/// A `Vec` of values renders as its values, or map it to views first.
+#[component]
+pub fn RenderValues() -> impl IntoView {
+    let values = vec![0, 1, 2];
+
+    view! {
+        // this will just render 012
+        <p>{values.clone()}</p>
+        // or we can wrap them in <li>
+        <ul>
+            {values.into_iter()
+                .map(|n| view! {
+                    <li>{n}</li>
+                })
+                .collect_view()}
+        </ul>
+    }
+}
+
+/// Iterating over more complex data with <For/>, updating rows in place.
+#[component]
+pub fn ForExample() -> impl IntoView {
+    #[derive(Debug, Clone)]
+    struct DatabaseEntry {
+        key: String,
+        value: i32
+    }
+
+    let (data, set_data) = create_signal(vec![
+        DatabaseEntry { key: "foo".to_string(), value: 10 },
+        DatabaseEntry { key: "bar".to_string(), value: 20 },
+        DatabaseEntry { key: "baz".to_string(), value: 15 }
+    ]);
+
+    view! {
+        // when we click, update each row,
+        // doubling its value
+        <button on:click=move |_| {
+            set_data.update(|data| {
+                for row in data {
+                    // every time data changes each memo will be recalulated. If its
+                    // value has changed, it will update its text node, withoug rerendering the whole row
+                    row.value *= 2;
+                }
+            });
+            // log the new value of the signal
+            logging::log!("{:?}", data.get());
+        }>
+            "Update Values"
+        </button>
+        // iterate over the rows and display each value
+        <For
+            // convert the data signal into an enumerated iterator
+            each=move || data().into_iter().enumerate()
+            key=|(_, state)| state.key.clone()
+            
+            // use the children prop explicitly, to make it easier to run some non-view code
+            children=move |(index, _)| {
+                // define a value memo and use that in the view.  This value field doesn't actually
+                // use the child being passed into each row. instead it uses the index and reaches
+                // into the original data to get the value;
+                let value = create_memo(move |_| {
+                    data.with(|data| data.get(index).map(|d| d.value).unwrap_or(0))
+                });
+                view! {
+                    <p>{value}</p>
+                }
+            }
+        />
+        
+    }
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+use leptos::*;
+use leptos_router::*;
+
+use super::transition_component::inportant_api_call;
+use crate::{
+    loaders::{use_loader_cache, LoaderCache, RouteLoader, SharedLoad},
+    params::{use_typed_query, InRange},
+    routing::RoutingMode,
+};
+
+// the open tab lives in the URL, as ?tab=1, so a link can point at it
+#[derive(Params, Clone, PartialEq)]
+struct TabQuery {
+    tab: Option<InRange<0, 2>>,
+}
+
+// no ?tab= (or one that doesn't parse) is the first tab
+fn tab_of(query: &ParamsMap) -> usize {
+    TabQuery::from_map(query).ok().and_then(|query| query.tab).map_or(0, |InRange(tab)| tab as usize)
+}
+
+// each tab's user is only loaded once, whether that's by opening the tab or by hovering over it
+fn user(cache: &LoaderCache, tab: usize) -> SharedLoad<String> {
+    cache.load(&format!("user/{tab}"), move || inportant_api_call(tab))
+}
+
+/// Starts loading the open tab's user as soon as the route matches, and a tab's
+/// user as soon as its link is hovered.
+pub const LOADER: RouteLoader = RouteLoader {
+    data: || Loader::from(load_tab_user),
+    prefetch: |cache, _, query| _ = user(cache, tab_of(query)),
+};
+
+// the route's data: the user of whichever tab ?tab= points at
+fn load_tab_user() -> Resource<usize, String> {
+    let query = use_query_map();
+    // looked up now, while the route is being set up; the fetcher runs long after
+    let cache = use_loader_cache();
+    create_resource(move || query.with(tab_of), move |tab| user(&cache, tab))
+}
+
+/// The tabs from TransitionComponent again, but the user of a tab is loaded by
+/// its route rather than by the view, and starts loading as soon as its link is hovered.
+#[component]
+pub fn LoaderComponent() -> impl IntoView {
+    let query = use_typed_query::<TabQuery>();
+    let tab = move || query().ok().and_then(|query| query.tab).map_or(0, |InRange(tab)| tab as usize);
+    let location = use_location();
+    let mode = RoutingMode::current();
+    let tab_href = move |tab: usize| move || mode.href(&format!("{}?tab={tab}", location.pathname.get()));
+
+    // loaded by the route before this view rendered, and reloaded every time 'tab' changes
+    let user_data = use_route_data::<Resource<usize, String>>().expect("the route's loader to have run");
+
+    view! {
+        // links rather than buttons: hovering one starts loading its tab
+        <div class="buttons">
+            <a href=tab_href(0) class:selected=move || tab() == 0>"Tab A"</a>
+            <a href=tab_href(1) class:selected=move || tab() == 1>"Tab B"</a>
+            <a href=tab_href(2) class:selected=move || tab() == 2>"Tab C"</a>
+        </div>
+
+        <Transition fallback=move || view! { <p>"Loading initial data..."</p> }>
+            <p> {move || user_data.get()} </p>
+        </Transition>
+
+        {move || if user_data.loading().get() {
+            "Hang on..."
+        } else {
+            ""
+        }}
+    }
+}
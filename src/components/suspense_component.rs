@@ -1,8 +1,9 @@
-use gloo_timers::future::TimeoutFuture;
 use leptos::*;
 
+use crate::sleep::sleep;
+
 async fn important_api_call(name: String) -> String {
-    TimeoutFuture::new(1_000).await;
+    sleep(1_000).await;
     name.to_ascii_uppercase()
 }
 
@@ -1,8 +1,9 @@
-use gloo_timers::future::TimeoutFuture;
 use leptos::*;
 
-async fn inportant_api_call(id: usize) -> String {
-    TimeoutFuture::new(1_000).await;
+use crate::sleep::sleep;
+
+pub async fn inportant_api_call(id: usize) -> String {
+    sleep(1_000).await;
 
     match id {
         0 => "Alice",
@@ -0,0 +1,16 @@
+// the examples are also used by the standalone app bundles in src/bin, so they live in a library
+pub mod apps;
+pub mod auth;
+pub mod breadcrumbs;
+pub mod components;
+pub mod loaders;
+pub mod locale;
+pub mod navigation_focus;
+pub mod navigation_progress;
+pub mod not_found;
+pub mod params;
+pub mod redirects;
+pub mod routing;
+pub mod sleep;
+pub mod transition_outlet;
+pub mod tutorial;
@@ -0,0 +1,147 @@
+//! Route data loaders.
+//!
+//! A route can load its data itself instead of leaving that to its view: the
+//! `data` of a <Route/> runs as soon as the router has matched the route, before
+//! the view renders, and the view picks up what it returned with
+//! `use_route_data()`. Every nested route has its own, so a layout and the tab
+//! inside it load at the same time rather than one after the other.
+//!
+//! The loads go through a [`LoaderCache`], so a link can start one before it's
+//! even clicked: <Prefetch/> does that for any link that is hovered or focused,
+//! for the routes registered with [`provide_prefetch`].
+use std::{any::Any, cell::RefCell, collections::HashMap, future::Future, rc::Rc};
+
+use futures::future::{FutureExt, LocalBoxFuture, Shared};
+use leptos::*;
+use leptos_router::*;
+use wasm_bindgen::JsCast;
+
+use crate::locale::use_canonical;
+use crate::routing::RoutingMode;
+
+/// A load that has started, which any number of routes and views can await.
+pub type SharedLoad<T> = Shared<LocalBoxFuture<'static, T>>;
+
+/// Every load so far, by key, shared by everything beneath [`provide_loaders`].
+///
+/// Get it with [`use_loader_cache`] while the component or the route's `data` is
+/// set up, not from inside a fetcher or an event handler: by the time those run
+/// there's no component around to look up the context from.
+#[derive(Clone, Default)]
+pub struct LoaderCache(Rc<RefCell<HashMap<String, Rc<dyn Any>>>>);
+
+// what a link to a route starts loading, from the link's params and query
+type PrefetchFn = fn(&LoaderCache, &ParamsMap, &ParamsMap);
+
+// every route that can be prefetched
+#[derive(Clone, Default)]
+struct Prefetchers(Rc<RefCell<Vec<(Matcher, PrefetchFn)>>>);
+
+/// Makes room for loads and prefetches. The cache has to outlive the routes that
+/// fill it, or a page prefetched on hover would be gone again once it's opened.
+pub fn provide_loaders() {
+    provide_context(LoaderCache::default());
+    provide_context(Prefetchers::default());
+}
+
+/// The cache provided by [`provide_loaders`]. Without one, loads still work, but
+/// aren't shared with anything else.
+pub fn use_loader_cache() -> LoaderCache {
+    use_context().unwrap_or_default()
+}
+
+impl LoaderCache {
+    /// Starts loading `key` with `fetch`, unless it's loaded or loading already, in
+    /// which case that load is returned instead. Loads are kept for as long as the
+    /// page is open, so only use this for data that doesn't change underneath you.
+    pub fn load<T, Fu>(&self, key: &str, fetch: impl FnOnce() -> Fu) -> SharedLoad<T>
+    where
+        T: Clone + 'static,
+        Fu: Future<Output = T> + 'static,
+    {
+        if let Some(load) = self.0.borrow().get(key).and_then(|load| load.downcast_ref::<SharedLoad<T>>()) {
+            return load.clone();
+        }
+        let load = fetch().boxed_local().shared();
+        self.0.borrow_mut().insert(key.to_string(), Rc::new(load.clone()));
+        // a shared future only runs while something awaits it, and nothing might until the link is clicked
+        spawn_local(load.clone().map(|_| ()));
+        load
+    }
+}
+
+/// What a route loads, for routes that come from a list, like the examples.
+#[derive(Clone, Copy)]
+pub struct RouteLoader {
+    /// starts the load for the route that was just matched; this is the route's `data`
+    pub data: fn() -> Loader,
+    /// starts the same load for a link to the route, from the link's params and query
+    pub prefetch: PrefetchFn,
+}
+
+/// Makes links to `pattern`, like `/contacts/:id`, run `prefetch` when they're
+/// hovered or focused. It gets the cache to load into, and the link's params and query.
+pub fn provide_prefetch(pattern: &str, prefetch: PrefetchFn) {
+    let Prefetchers(prefetchers) = expect_context::<Prefetchers>();
+    prefetchers.borrow_mut().push((Matcher::new(pattern), prefetch));
+}
+
+/// Prefetches the route of any link on the page as soon as it's hovered or
+/// focused, which is usually a good while before the click.
+#[component]
+pub fn Prefetch() -> impl IntoView {
+    let Prefetchers(prefetchers) = expect_context::<Prefetchers>();
+    let cache = use_loader_cache();
+    // the prefetches are registered for the routes as they're written, not their localized URLs
+    let canonical = use_canonical();
+
+    // an effect, so it only listens in the browser
+    create_effect(move |_| {
+        let prefetchers = prefetchers.clone();
+        let canonical = canonical.clone();
+        let cache = cache.clone();
+        let prefetch = move |target: Option<web_sys::EventTarget>| {
+            let link = target
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .and_then(|element| element.closest("a[href]").ok().flatten())
+                .and_then(|link| link_route(&link.unchecked_into()));
+            let Some(link) = link else { return };
+            let pathname = canonical(&link.pathname);
+            for (matcher, prefetch) in prefetchers.borrow().iter() {
+                if let Some(matched) = matcher.test(&pathname) {
+                    prefetch(&cache, &matched.params, &link.search_params);
+                }
+            }
+        };
+        let hover = window_event_listener(ev::mouseover, {
+            let prefetch = prefetch.clone();
+            move |ev| prefetch(ev.target())
+        });
+        // focus doesn't bubble, but focusin does
+        let focus = window_event_listener(ev::focusin, move |ev| prefetch(ev.target()));
+        on_cleanup(move || {
+            hover.remove();
+            focus.remove();
+        });
+    });
+}
+
+// the route a link goes to, in either routing mode. links off the site have none
+fn link_route(link: &web_sys::HtmlAnchorElement) -> Option<Url> {
+    let url = Url::try_from(link.href().as_str()).ok()?;
+    let here = window().location();
+    if url.origin != here.origin().ok()? {
+        return None;
+    }
+    match RoutingMode::detect() {
+        RoutingMode::Path => Some(url),
+        // the route is in the fragment, like index.html#/contacts/alice
+        RoutingMode::Hash => {
+            let route = url.hash.strip_prefix('#').filter(|route| route.starts_with('/'))?;
+            if url.pathname != here.pathname().ok()? {
+                return None;
+            }
+            Url::try_from(route).ok()
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! Localized URLs.
+//!
+//! Every route is there once for each locale: as it's written, like
+//! `/contacts/:id`, for English, and under the locale's prefix with its static
+//! segments translated, like `/de/kontakte/:id`, for German. The views are the
+//! same, so a page doesn't need to know which of its URLs it was opened by.
+//!
+//! The locale of the page is the one its URL is in. Links keep to it: the <A/>
+//! from here takes the same hrefs as the router's, `/contacts` or `edit`, and
+//! turns them into the URL of that page in the current locale.
+use std::{collections::BTreeMap, rc::Rc};
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::params::{fill_in, match_pattern};
+use crate::routing::RoutingMode;
+
+/// A language the URLs come in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    De,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::De];
+
+    /// like `de`, for `lang` and `hreflang`
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+        }
+    }
+
+    /// what the locale calls itself, for the switcher
+    pub fn name(self) -> &'static str {
+        match self {
+            Locale::En => "English",
+            Locale::De => "Deutsch",
+        }
+    }
+
+    /// the first segment of the locale's URLs. English, the default, has none
+    pub fn prefix(self) -> Option<&'static str> {
+        match self {
+            Locale::En => None,
+            Locale::De => Some("de"),
+        }
+    }
+
+    /// the locale a path is in, from its first segment
+    pub fn of(path: &str) -> Locale {
+        let first = path.trim_start_matches('/').split('/').next().unwrap_or_default();
+        Locale::ALL.iter().copied().find(|locale| locale.prefix() == Some(first)).unwrap_or(Locale::En)
+    }
+
+    // a static path segment in this locale. segments without a translation stay as they are
+    fn translate(self, segment: &str) -> &str {
+        match self {
+            Locale::En => segment,
+            Locale::De => SEGMENTS.iter().find(|(en, _)| *en == segment).map_or(segment, |(_, de)| de),
+        }
+    }
+}
+
+// every path segment that's translated, in English and German
+const SEGMENTS: &[(&str, &str)] = &[
+    ("address-book", "adressbuch"),
+    ("contacts", "kontakte"),
+    ("conversations", "unterhaltungen"),
+    ("duplicates", "duplikate"),
+    ("edit", "bearbeiten"),
+    ("examples", "beispiele"),
+    ("form", "formular"),
+    ("import", "importieren"),
+    ("login", "anmelden"),
+    ("new", "neu"),
+    ("people", "personen"),
+    ("tag", "schlagwort"),
+];
+
+// the full pattern of a route, the way it's written
+struct RoutePattern {
+    pattern: String,
+    // how specific the pattern is, so /contacts/new wins over /contacts/:id, and
+    // anything wins over a wildcard
+    specificity: (usize, bool),
+}
+
+impl RoutePattern {
+    fn new(pattern: String) -> Self {
+        Self {
+            specificity: (
+                pattern.split('/').filter(|s| !s.is_empty() && !s.starts_with([':', '*'])).count(),
+                !pattern.contains('*'),
+            ),
+            pattern,
+        }
+    }
+
+    // the params of `path`, if it's a URL of this route in `locale`. a static segment may be
+    // in English as well: that's what a relative link like `edit` adds on to a localized URL
+    fn test(&self, path: &str, locale: Locale) -> Option<BTreeMap<String, String>> {
+        let path = match locale.prefix() {
+            Some(prefix) => {
+                let rest = path.trim_start_matches('/').strip_prefix(prefix)?;
+                rest.is_empty().then_some(rest).or_else(|| rest.strip_prefix('/'))?
+            }
+            None => path,
+        };
+        match_pattern(&self.pattern, path, |segment, part| segment == part || segment == locale.translate(part))
+    }
+}
+
+// every route's full pattern, which is the same in every locale but for the words
+#[derive(Clone)]
+struct LocalizedPaths(Rc<Vec<RoutePattern>>);
+
+/// `routes`, and a copy of them for every other locale, to hand to <Routes/>.
+///
+/// It also keeps each route's pattern for <A/> and the <LocaleSwitcher/>, which
+/// translate links outside of the <Routes/>, so it has to run before the <Router/>.
+pub fn localize_routes(routes: Vec<View>) -> Vec<View> {
+    let definitions = routes
+        .iter()
+        .filter_map(|route| route.as_transparent()?.downcast_ref::<RouteDefinition>().cloned())
+        .collect::<Vec<_>>();
+
+    let mut patterns = vec![];
+    for definition in &definitions {
+        collect_patterns(definition, "", &mut patterns);
+    }
+    provide_context(LocalizedPaths(Rc::new(patterns.into_iter().map(RoutePattern::new).collect())));
+
+    let localized_routes = Locale::ALL
+        .iter()
+        .filter(|locale| locale.prefix().is_some())
+        .flat_map(|&locale| {
+            definitions.iter().map(move |definition| {
+                let mut localized = translate_route(definition, locale);
+                localized.path = localize_pattern(&definition.path, locale);
+                localized.into_view()
+            })
+        })
+        .collect::<Vec<_>>();
+    routes.into_iter().chain(localized_routes).collect()
+}
+
+/// A full route pattern, like `/contacts/:id`, in `locale`, like `/de/kontakte/:id`.
+pub fn localize_pattern(pattern: &str, locale: Locale) -> String {
+    let translated = translate_path(pattern, locale);
+    match locale.prefix() {
+        Some(prefix) => join(&format!("/{prefix}"), &translated),
+        None => translated,
+    }
+}
+
+/// `path`, a URL of the route `pattern`, for the same page in `locale`. For
+/// when there's no router around to ask, like in the prerender.
+pub fn localize_path(pattern: &str, path: &str, locale: Locale) -> String {
+    let params = RoutePattern::new(pattern.to_string()).test(path, Locale::En).unwrap_or_default();
+    fill_in(&localize_pattern(pattern, locale), &params)
+}
+
+fn translate_path(path: &str, locale: Locale) -> String {
+    path.split('/')
+        .map(|segment| if segment.starts_with([':', '*']) { segment } else { locale.translate(segment) })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// the route and everything nested in it, with their static segments translated
+fn translate_route(definition: &RouteDefinition, locale: Locale) -> RouteDefinition {
+    let mut definition = definition.clone();
+    definition.path = translate_path(&definition.path, locale);
+    definition.children = definition.children.iter().map(|child| translate_route(child, locale)).collect();
+    definition
+}
+
+// the full pattern of the route and of every route nested in it
+fn collect_patterns(definition: &RouteDefinition, parent: &str, patterns: &mut Vec<String>) {
+    let pattern = join(parent, &definition.path);
+    for child in definition.children.iter() {
+        collect_patterns(child, &pattern, patterns);
+    }
+    patterns.push(pattern);
+}
+
+// nested paths are relative to their parent, with or without a leading /
+fn join(parent: &str, path: &str) -> String {
+    let path = path.trim_matches('/');
+    match (parent.trim_end_matches('/'), path) {
+        ("", "") => "/".to_string(),
+        (parent, "") => parent.to_string(),
+        (parent, path) => format!("{parent}/{path}"),
+    }
+}
+
+impl LocalizedPaths {
+    // the most specific route that `path` is a URL of, in `locale`, and its params
+    fn find(&self, path: &str, locale: Locale) -> Option<(&RoutePattern, BTreeMap<String, String>)> {
+        self.0
+            .iter()
+            .filter_map(|pattern| Some((pattern, pattern.test(path, locale)?)))
+            .max_by_key(|(pattern, _)| pattern.specificity)
+    }
+
+    /// The same path in English, the way the routes are written.
+    fn canonical(&self, path: &str) -> String {
+        let locale = Locale::of(path);
+        if locale == Locale::En {
+            return path.to_string();
+        }
+        match self.find(path, locale) {
+            Some((pattern, params)) => fill_in(&pattern.pattern, &params),
+            // no route has it, so it's a 404 either way
+            None => path.to_string(),
+        }
+    }
+
+    /// `href`, a path with a query and fragment if it likes, in `locale`.
+    fn localize(&self, href: &str, locale: Locale) -> String {
+        let split = href.find(['?', '#']).unwrap_or(href.len());
+        let (path, rest) = href.split_at(split);
+        let canonical = self.canonical(path);
+        let localized = match self.find(&canonical, Locale::En) {
+            Some((pattern, params)) if locale != Locale::En => fill_in(&localize_pattern(&pattern.pattern, locale), &params),
+            _ => canonical,
+        };
+        format!("{localized}{rest}")
+    }
+}
+
+/// The locale of the current page.
+pub fn use_locale() -> Memo<Locale> {
+    let pathname = use_location().pathname;
+    create_memo(move |_| pathname.with(|path| Locale::of(path)))
+}
+
+/// Turns a path, like `/contacts/alice/edit` or one already localized, into the
+/// one for the same page in the current locale, for links and navigating.
+/// Outside of the localized routes, like in a standalone app, paths stay as they are.
+pub fn use_localize() -> impl Fn(&str) -> String + Clone + 'static {
+    let paths = use_context::<LocalizedPaths>();
+    let locale = use_locale();
+    move |href: &str| match &paths {
+        Some(paths) => paths.localize(href, locale.get_untracked()),
+        None => href.to_string(),
+    }
+}
+
+/// The path `path` stands for, the way the routes are written: `/de/kontakte`
+/// is `/contacts`. Paths that aren't localized stay as they are.
+pub fn use_canonical() -> impl Fn(&str) -> String + Clone + 'static {
+    let paths = use_context::<LocalizedPaths>();
+    move |path: &str| match &paths {
+        Some(paths) => paths.canonical(path),
+        None => path.to_string(),
+    }
+}
+
+/// A link to `href` in the current locale: like the router's <A/>, but
+/// `/contacts` goes to `/de/kontakte` on a German page.
+#[component]
+pub fn A<H>(
+    /// an absolute path, or one relative to the current route, like the router's <A/> takes
+    href: H,
+    /// only mark the link active for exactly its own URL, not the ones nested beneath it
+    #[prop(optional)]
+    exact: bool,
+    #[prop(optional, into)]
+    class: Option<AttributeValue>,
+    children: Children,
+) -> impl IntoView
+where
+    H: ToHref + 'static,
+{
+    let resolved = use_resolved_path(move || href.to_href()());
+    let localize = use_localize();
+    let locale = use_locale();
+    let href = move || {
+        // a new locale is a new URL, even when the href is the same
+        locale.track();
+        resolved.get().map(|href| localize(&href)).unwrap_or_default()
+    };
+    match (RoutingMode::current(), class) {
+        (RoutingMode::Path, Some(class)) => {
+            view! { <leptos_router::A href exact class>{children()}</leptos_router::A> }.into_view()
+        }
+        (RoutingMode::Path, None) => view! { <leptos_router::A href exact>{children()}</leptos_router::A> }.into_view(),
+        // the router's <A/> can only write out the path itself, so this is the same link with `#` in front
+        (RoutingMode::Hash, class) => {
+            let location = use_location();
+            // which pages the link counts as active on, the same way as the router's
+            let is_active = create_memo({
+                let href = href.clone();
+                move |_| {
+                    let href = href();
+                    let path = href.split(['?', '#']).next().unwrap_or_default().to_lowercase();
+                    location.pathname.with(|here| {
+                        let here = here.to_lowercase();
+                        if exact {
+                            here == path
+                        } else {
+                            std::iter::zip(here.split('/'), path.split('/')).all(|(here, path)| here == path)
+                        }
+                    })
+                }
+            });
+            view! {
+                <a href=move || RoutingMode::Hash.href(&href()) class=class aria-current=move || is_active().then_some("page")>
+                    {children()}
+                </a>
+            }
+            .into_view()
+        }
+    }
+}
+
+/// Links to the page that's open, in each of the other locales.
+#[component]
+pub fn LocaleSwitcher() -> impl IntoView {
+    let locale = use_locale();
+    let location = use_location();
+    let paths = use_context::<LocalizedPaths>();
+
+    // the page says which language it's in
+    create_effect(move |_| {
+        if let Some(html) = document().document_element() {
+            _ = html.set_attribute("lang", locale().code());
+        }
+    });
+
+    view! {
+        <span class="locale-switcher">
+            {Locale::ALL.iter().map(|&other| {
+                let paths = paths.clone();
+                // the same page, with the same query, in the other locale
+                let href = move || {
+                    let here = format!("{}{}", location.pathname.get(), match location.search.get() {
+                        search if search.is_empty() => String::new(),
+                        search => format!("?{search}"),
+                    });
+                    let href = match &paths {
+                        Some(paths) => paths.localize(&here, other),
+                        None => here,
+                    };
+                    RoutingMode::current().href(&href)
+                };
+                view! {
+                    " "
+                    <a href=href hreflang=other.code() lang=other.code() aria-current=move || (locale() == other).then_some("true")>
+                        {other.name()}
+                    </a>
+                }
+            }).collect_view()}
+        </span>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(patterns: &[&str]) -> LocalizedPaths {
+        LocalizedPaths(Rc::new(patterns.iter().map(|pattern| RoutePattern::new(pattern.to_string())).collect()))
+    }
+
+    const PATTERNS: &[&str] = &[
+        "/",
+        "/contacts",
+        "/contacts/new",
+        "/contacts/:id",
+        "/contacts/:id/edit",
+        "/contacts/tag/:tag",
+        "/examples/:slug",
+        "/*any",
+    ];
+
+    #[test]
+    fn the_locale_is_the_first_segment() {
+        assert_eq!(Locale::of("/de/kontakte"), Locale::De);
+        assert_eq!(Locale::of("de"), Locale::De);
+        assert_eq!(Locale::of("/contacts"), Locale::En);
+        assert_eq!(Locale::of("/denmark"), Locale::En);
+        assert_eq!(Locale::of(""), Locale::En);
+    }
+
+    #[test]
+    fn patterns_in_each_locale() {
+        assert_eq!(localize_pattern("/contacts/:id/edit", Locale::De), "/de/kontakte/:id/bearbeiten");
+        assert_eq!(localize_pattern("/contacts/:id/edit", Locale::En), "/contacts/:id/edit");
+        assert_eq!(localize_pattern("/", Locale::De), "/de");
+        // a param named like a segment is still a param, and untranslated segments stay
+        assert_eq!(localize_pattern("/tag/:contacts/*rest", Locale::De), "/de/schlagwort/:contacts/*rest");
+        assert_eq!(localize_pattern("/apps", Locale::De), "/de/apps");
+    }
+
+    #[test]
+    fn paths_without_a_router() {
+        assert_eq!(localize_path("/contacts/:id/edit", "/contacts/alice/edit", Locale::De), "/de/kontakte/alice/bearbeiten");
+        assert_eq!(localize_path("/contacts/tag/:tag", "/contacts/tag/new", Locale::De), "/de/kontakte/schlagwort/new");
+        assert_eq!(localize_path("/contacts", "/contacts", Locale::En), "/contacts");
+    }
+
+    #[test]
+    fn from_english_to_german() {
+        let paths = paths(PATTERNS);
+        assert_eq!(paths.localize("/contacts/alice?q=al#notes", Locale::De), "/de/kontakte/alice?q=al#notes");
+        // the most specific route wins: `new` is a page, not an id
+        assert_eq!(paths.localize("/contacts/new", Locale::De), "/de/kontakte/neu");
+        // a param that happens to be a translated word isn't translated
+        assert_eq!(paths.localize("/contacts/edit", Locale::De), "/de/kontakte/edit");
+        assert_eq!(paths.localize("/", Locale::De), "/de");
+        assert_eq!(paths.localize("/examples/form", Locale::De), "/de/beispiele/form");
+    }
+
+    #[test]
+    fn from_german_to_english() {
+        let paths = paths(PATTERNS);
+        assert_eq!(paths.canonical("/de/kontakte/alice/bearbeiten"), "/contacts/alice/edit");
+        assert_eq!(paths.canonical("/de"), "/");
+        assert_eq!(paths.localize("/de/kontakte/neu?x=1", Locale::En), "/contacts/new?x=1");
+        // a relative link adds on English segments, which are understood too
+        assert_eq!(paths.canonical("/de/kontakte/alice/edit"), "/contacts/alice/edit");
+        // English paths are left as they are
+        assert_eq!(paths.canonical("/contacts/alice"), "/contacts/alice");
+    }
+
+    #[test]
+    fn round_trips() {
+        let paths = paths(PATTERNS);
+        for path in ["/", "/contacts", "/contacts/new", "/contacts/bob/edit", "/contacts/tag/work", "/nowhere/at/all"] {
+            let german = paths.localize(path, Locale::De);
+            assert_eq!(paths.localize(&german, Locale::En), path, "{path} by way of {german}");
+            assert_eq!(paths.localize(&german, Locale::De), german);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use leptos::*;
+use leptos_router::*;
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::breadcrumbs::use_page_title;
+
+// where the scroll positions are kept over a reload, for this tab only
+const POSITIONS_KEY: &str = "scroll-positions";
+// what marks a history entry as one we've seen before
+const ENTRY_KEY: &str = "scrollKey";
+
+type Positions = HashMap<String, (f64, f64)>;
+
+/// Puts the scroll position and the keyboard focus where they belong after the
+/// router has navigated, and tells screen readers which page this is now.
+///
+/// - following a link or submitting a form starts at the top of the new page
+/// - going back or forward returns to where that page was scrolled to before,
+///   which also works across a reload
+/// - a `#fragment`, like `/form#automatic`, scrolls to the element with that id
+/// - focus moves to the new page's main heading (or to the fragment's element),
+///   so the next Tab starts there instead of back in the nav
+///
+/// Give it the same `is_routing` signal as the <Router/>: the page is only laid out,
+/// and so only has the height to scroll to, once everything it loads has loaded.
+#[component]
+pub fn NavigationFocus(
+    /// whether the router is navigating right now
+    #[prop(into)]
+    is_routing: Signal<bool>,
+) -> impl IntoView {
+    let location = use_location();
+    let title = use_page_title();
+    let (announcement, set_announcement) = create_signal(String::new());
+    // the scroll position of every history entry, by its key
+    let positions = store_value(Positions::new());
+    // the entry the window's scroll position is noted down for; none while navigating
+    let current = store_value(None::<String>);
+
+    let settle = move |after_navigation: bool| {
+        let (key, revisited) = entry_key();
+        current.set_value(key.clone());
+        let target = fragment_target(&location.hash.get_untracked());
+
+        if after_navigation {
+            // focusing scrolls to the element, so this goes before the scroll below
+            if let Some(focus) = target.clone().or_else(main_heading) {
+                _ = focus.set_attribute("tabindex", "-1");
+                focus.unchecked_into::<web_sys::HtmlElement>().focus().ok();
+            }
+            set_announcement(format!("{}, page loaded", title.get_untracked()));
+        }
+
+        let position = key.filter(|_| revisited).and_then(|key| positions.with_value(|p| p.get(&key).copied()));
+        if let Some((x, y)) = position {
+            window().scroll_to_with_x_and_y(x, y);
+        } else if let Some(target) = target {
+            target.scroll_into_view();
+        } else if after_navigation {
+            window().scroll_to_with_x_and_y(0.0, 0.0);
+        }
+    };
+
+    create_effect(move |was_routing: Option<bool>| {
+        let routing = is_routing();
+        match was_routing {
+            // the page as it was loaded, or reloaded
+            None => {
+                // the browser would restore the position before the page has been rendered
+                _ = window().history().and_then(|history| history.set_scroll_restoration(web_sys::ScrollRestoration::Manual));
+                positions.set_value(load_positions());
+                let scroll = window_event_listener(ev::scroll, move |_| {
+                    if let Some(key) = current.get_value() {
+                        let position = (window().scroll_x().unwrap_or_default(), window().scroll_y().unwrap_or_default());
+                        positions.update_value(|positions| _ = positions.insert(key, position));
+                    }
+                });
+                let pagehide = window_event_listener(ev::pagehide, move |_| positions.with_value(save_positions));
+                on_cleanup(move || {
+                    scroll.remove();
+                    pagehide.remove();
+                });
+                request_animation_frame(move || settle(false));
+            }
+            // the router updates the URL and scrolls to the top right after this, so
+            // the new page is settled on the frame after that
+            Some(true) if !routing => request_animation_frame(move || settle(true)),
+            _ => {}
+        }
+        if routing {
+            // the new page is rendering, which scrolls the old one's entry around
+            current.set_value(None);
+        }
+        routing
+    });
+
+    view! {
+        <div class="visually-hidden" aria-live="polite" aria-atomic="true">{announcement}</div>
+    }
+}
+
+// the key of the current history entry, and whether it had one already. the
+// router pushes new entries without any state, so those get a key here
+fn entry_key() -> (Option<String>, bool) {
+    let history = window().history().ok();
+    let state = history.as_ref().and_then(|history| history.state().ok()).unwrap_or(JsValue::NULL);
+    if let Some(key) = js_sys::Reflect::get(&state, &ENTRY_KEY.into()).ok().and_then(|key| key.as_string()) {
+        return (Some(key), true);
+    }
+    // state someone else put there is left alone, and that entry goes without
+    if !state.is_null() && !state.is_undefined() {
+        return (None, false);
+    }
+    let key = uuid::Uuid::new_v4().to_string();
+    let state = js_sys::Object::new();
+    _ = js_sys::Reflect::set(&state, &ENTRY_KEY.into(), &key.as_str().into());
+    let keyed = history.is_some_and(|history| history.replace_state(&state, "").is_ok());
+    (keyed.then_some(key), false)
+}
+
+// the element a #fragment points at
+fn fragment_target(hash: &str) -> Option<web_sys::Element> {
+    let id = hash.strip_prefix('#').filter(|id| !id.is_empty())?;
+    let id = js_sys::decode_uri_component(id).ok().and_then(|id| id.as_string()).unwrap_or_else(|| id.to_string());
+    document().get_element_by_id(&id)
+}
+
+// the first heading of whatever the <Routes/> rendered, or all of it when there's none
+fn main_heading() -> Option<web_sys::Element> {
+    document().query_selector("main :is(h1, h2, h3)").ok().flatten().or_else(|| document().query_selector("main").ok().flatten())
+}
+
+fn load_positions() -> Positions {
+    let storage = window().session_storage().ok().flatten();
+    let saved = storage.and_then(|storage| storage.get_item(POSITIONS_KEY).ok().flatten());
+    saved.and_then(|saved| serde_json::from_str(&saved).ok()).unwrap_or_default()
+}
+
+fn save_positions(positions: &Positions) {
+    if let (Some(storage), Ok(json)) = (window().session_storage().ok().flatten(), serde_json::to_string(positions)) {
+        _ = storage.set_item(POSITIONS_KEY, &json);
+    }
+}
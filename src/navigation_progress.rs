@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+use leptos::{leptos_dom::helpers::IntervalHandle, *};
+
+use crate::components::progress_bar::ProgressBar;
+
+// the bar counts in tenths of a percent, so the trickle can take small steps
+const MAX: i16 = 1000;
+// where the bar starts, so it's visible the moment it appears
+const START: i32 = 80;
+// the trickle only ever creeps towards this, so the bar never looks done before it is
+const TRICKLE_LIMIT: i32 = 940;
+// how often the bar creeps forward while the next page loads
+const TRICKLE_EVERY: Duration = Duration::from_millis(200);
+// navigations that finish sooner than this don't show a bar at all
+const SHOW_AFTER: Duration = Duration::from_millis(100);
+// how long the full bar stays up once the page has loaded
+const LINGER: Duration = Duration::from_millis(300);
+
+/// A thin bar across the top of the page while the router is navigating.
+///
+/// Give the <Router/> a `set_is_routing` and pass the same signal in here. The
+/// router keeps it set until every resource read under the new route has loaded,
+/// so the bar covers slow pages too, not just the navigation itself. Meanwhile the
+/// bar trickles towards the end, a little slower every step, and fills up once
+/// the new page is shown.
+#[component]
+pub fn NavigationProgress(
+    /// whether the router is navigating right now
+    #[prop(into)]
+    is_routing: Signal<bool>,
+) -> impl IntoView {
+    let (progress, set_progress) = create_signal(0);
+    let (visible, set_visible) = create_signal(false);
+
+    // the effect hands its trickle interval to its next run, which stops it
+    create_effect(move |trickle: Option<Option<IntervalHandle>>| {
+        if let Some(Some(trickle)) = trickle {
+            trickle.clear();
+        }
+        if is_routing() {
+            set_timeout(
+                move || {
+                    if is_routing.get_untracked() && !visible.get_untracked() {
+                        set_progress(START);
+                        set_visible(true);
+                    }
+                },
+                SHOW_AFTER,
+            );
+            // each step covers a tenth of what's left, so the bar slows down as it goes
+            set_interval_with_handle(
+                move || {
+                    set_progress.update(|progress| {
+                        *progress = (*progress + ((TRICKLE_LIMIT - *progress) / 10).max(1)).min(TRICKLE_LIMIT)
+                    })
+                },
+                TRICKLE_EVERY,
+            )
+            .ok()
+        } else {
+            if visible.get_untracked() {
+                // fill the bar up, and hide it once that's been seen
+                set_progress(MAX.into());
+                set_timeout(
+                    move || {
+                        // unless the next navigation has started in the meantime
+                        if !is_routing.get_untracked() {
+                            set_visible(false);
+                            set_progress(0);
+                        }
+                    },
+                    LINGER,
+                );
+            } else {
+                set_progress(0);
+            }
+            None
+        }
+    });
+
+    view! {
+        <Show when=visible>
+            <div class="navigation-progress">
+                <ProgressBar progress max=MAX />
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::components::error_handling::ErrorList;
+use crate::locale::{use_canonical, A};
+
+/// A component couldn't find the thing its route points at, e.g. `/contacts/:id`
+/// with an id nobody has.
+///
+/// Render an `Err(NotFound)` inside a <NotFoundBoundary/> and the boundary shows a
+/// not-found page in place of whatever the component would have drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotFound {
+    /// what kind of thing is missing, e.g. "contact"
+    pub kind: &'static str,
+    /// how it was looked up, usually the id from the URL
+    pub id: String,
+    /// where to go instead, e.g. the list the thing would have been in
+    pub back_href: String,
+}
+
+impl NotFound {
+    pub fn new(kind: &'static str, id: impl Into<String>, back_href: impl Into<String>) -> Self {
+        Self { kind, id: id.into(), back_href: back_href.into() }
+    }
+}
+
+impl fmt::Display for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "there is no {} called {:?}", self.kind, self.id)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// An <ErrorBoundary/> that turns a [`NotFound`] into a not-found page. Any other
+/// error is listed by an <ErrorList/>.
+#[component]
+pub fn NotFoundBoundary(children: Children) -> impl IntoView {
+    view! {
+        <ErrorBoundary
+            fallback=|errors| move || {
+                let not_found = errors.with(|errors| {
+                    errors.iter().find_map(|(_, error)| error.downcast_ref::<NotFound>().cloned())
+                });
+                match not_found {
+                    Some(not_found) => view! { <NotFoundView not_found /> }.into_view(),
+                    None => view! { <ErrorList errors/> }.into_view(),
+                }
+            }
+        >
+            {children()}
+        </ErrorBoundary>
+    }
+}
+
+#[component]
+fn NotFoundView(not_found: NotFound) -> impl IntoView {
+    view! {
+        <div class="not-found">
+            <h4>{format!("{} not found", capitalize(not_found.kind))}</h4>
+            <p>{format!("Sorry, {}.", not_found)}</p>
+            <A href=not_found.back_href>"Go back"</A>
+        </div>
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// The catch-all route: says the URL doesn't exist and suggests the closest ones that do.
+///
+/// Mount it last as `<Route path="/*any" .../>`; the router prefers every other
+/// route over a wildcard, so it only shows when nothing else matches.
+#[component]
+pub fn NotFoundPage(
+    /// the paths worth suggesting, e.g. every top-level page
+    #[prop(into)]
+    paths: Vec<String>,
+) -> impl IntoView {
+    let location = use_location();
+    let pathname = move || location.pathname.get();
+    // the paths are the routes as they're written, so a localized URL is compared as one of those
+    let canonical = use_canonical();
+    let suggestions = move || suggest(&canonical(&pathname()), &paths);
+
+    view! {
+        <div class="not-found">
+            <h3>"Page not found"</h3>
+            <p>{move || format!("Nothing lives at {}.", pathname())}</p>
+            <Show
+                when={
+                    let suggestions = suggestions.clone();
+                    move || !suggestions().is_empty()
+                }
+                fallback=|| view! { <A href="/">"Go home"</A> }
+            >
+                <p>"Did you mean:"</p>
+                <ul>
+                    {
+                        let suggestions = suggestions.clone();
+                        move || suggestions()
+                            .into_iter()
+                            .map(|path| view! { <li><A href=path.clone()>{path.clone()}</A></li> })
+                            .collect_view()
+                    }
+                </ul>
+            </Show>
+        </div>
+    }
+}
+
+// how many suggestions to show at most
+const SUGGESTIONS: usize = 3;
+
+/// The `paths` closest to `pathname`, closest first: any that `pathname` is
+/// inside of (like `/contacts` for `/contacts/nope/nope`), then those a few typos away.
+pub fn suggest(pathname: &str, paths: &[String]) -> Vec<String> {
+    let pathname = pathname.trim_end_matches('/').to_lowercase();
+    let mut scored = paths
+        .iter()
+        .filter_map(|path| {
+            let candidate = path.trim_end_matches('/').to_lowercase();
+            if !candidate.is_empty() && pathname.starts_with(&format!("{candidate}/")) {
+                // the longer the shared prefix, the closer the parent
+                return Some((0, usize::MAX - candidate.len(), path));
+            }
+            let distance = edit_distance(&pathname, &candidate);
+            (distance <= (candidate.len() / 3).max(2)).then_some((1, distance, path))
+        })
+        .collect::<Vec<_>>();
+    scored.sort();
+    scored.into_iter().take(SUGGESTIONS).map(|(_, _, path)| path.clone()).collect()
+}
+
+// the levenshtein distance: how many single character edits turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abc", ""), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("/contacts", "/contcats"), 2);
+        // characters, not bytes
+        assert_eq!(edit_distance("/grüße", "/grüsse"), 2);
+    }
+
+    #[test]
+    fn suggests_typos_closest_first() {
+        let routes = paths(&["/", "/contacts", "/conversations", "/examples", "/login"]);
+        assert_eq!(suggest("/contcats", &routes), ["/contacts"]);
+        assert_eq!(suggest("/Contacts/", &routes), ["/contacts"]);
+        assert_eq!(suggest("/logn", &routes), ["/login"]);
+        assert!(suggest("/nothing-like-it", &routes).is_empty());
+        // however short a path is, a typo or two away still counts
+        assert_eq!(suggest("/x", &paths(&["/"])), ["/"]);
+    }
+
+    #[test]
+    fn suggests_the_closest_parent_first() {
+        let routes = paths(&["/contacts", "/contacts/new", "/contacts/import", "/examples"]);
+        assert_eq!(suggest("/contacts/new/nope", &routes), ["/contacts/new", "/contacts"]);
+        assert_eq!(suggest("/contacts/nope/nope", &routes)[0], "/contacts");
+    }
+
+    #[test]
+    fn suggests_at_most_a_few() {
+        let routes = paths(&["/a1", "/a2", "/a3", "/a4", "/a5"]);
+        assert_eq!(suggest("/a", &routes).len(), SUGGESTIONS);
+    }
+}
@@ -0,0 +1,297 @@
+//! Typed route params and query strings.
+//!
+//! Instead of picking strings out of `use_params_map()` and parsing them by hand,
+//! a component describes what it expects as a struct and gets it back parsed:
+//!
+//! ```ignore
+//! #[derive(Params, Clone, PartialEq)]
+//! struct PageQuery {
+//!     // ?page=3, or nothing at all
+//!     page: Option<InRange<1, 1000>>,
+//! }
+//!
+//! let query = use_typed_query::<PageQuery>();
+//! ```
+//!
+//! A missing or malformed value is an `Err`, so rendering `query()` inside an
+//! <ErrorBoundary/> shows what's wrong with the URL instead of the page.
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use leptos::*;
+use leptos_router::*;
+
+/// Why a route's params or query string didn't parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidParams(pub String);
+
+impl fmt::Display for InvalidParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid URL: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidParams {}
+
+impl From<ParamsError> for InvalidParams {
+    fn from(error: ParamsError) -> Self {
+        match error {
+            ParamsError::MissingParam(name) => Self(format!("{name} is missing")),
+            // the router's own message for this is just "failed to deserialize parameters"
+            ParamsError::Params(error) => Self(error.to_string()),
+        }
+    }
+}
+
+/// A single value that isn't what the param should hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidParam {
+    pub value: String,
+    /// what it should have been, e.g. "a number from 1 to 10"
+    pub expected: String,
+}
+
+impl InvalidParam {
+    pub fn new(value: impl Into<String>, expected: impl Into<String>) -> Self {
+        Self { value: value.into(), expected: expected.into() }
+    }
+}
+
+impl fmt::Display for InvalidParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} isn't {}", self.value, self.expected)
+    }
+}
+
+impl std::error::Error for InvalidParam {}
+
+/// The params of the current route, parsed into `T`.
+pub fn use_typed_params<T>() -> Memo<Result<T, InvalidParams>>
+where
+    T: Params + PartialEq + 'static,
+{
+    let route = use_route();
+    create_memo(move |_| route.params().with(T::from_map).map_err(InvalidParams::from))
+}
+
+/// The query string, parsed into `T`.
+///
+/// An empty value counts as a missing one, because that's what a <Form/> sends
+/// for a field that was left blank: `?number=` is the same as no `?number` at all.
+pub fn use_typed_query<T>() -> Memo<Result<T, InvalidParams>>
+where
+    T: Params + PartialEq + 'static,
+{
+    let query = use_query_map();
+    create_memo(move |_| {
+        query.with(|query| {
+            let filled_in = query.0.iter().filter(|(_, value)| !value.is_empty());
+            let query = ParamsMap(filled_in.map(|(key, value)| (key.clone(), value.clone())).collect());
+            T::from_map(&query).map_err(InvalidParams::from)
+        })
+    })
+}
+
+/// Params by name, wherever they came from: the router's `ParamsMap`, or a plain
+/// map like the one [`match_pattern`] returns.
+pub trait ParamLookup {
+    fn param(&self, name: &str) -> Option<&str>;
+}
+
+impl ParamLookup for ParamsMap {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.get(name).map(String::as_str)
+    }
+}
+
+impl ParamLookup for BTreeMap<String, String> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.get(name).map(String::as_str)
+    }
+}
+
+/// A route pattern with its params filled in: `/contacts/:id` with `id=alice` is
+/// `/contacts/alice`. A wildcard like `*rest` is filled in the same way, and a
+/// param with no value is left out, so `/contacts/:id?` without one is `/contacts`.
+pub fn fill_in(pattern: &str, params: &impl ParamLookup) -> String {
+    let path = pattern
+        .split('/')
+        .filter_map(|segment| match segment.strip_prefix(':').or_else(|| segment.strip_prefix('*')) {
+            Some(name) => {
+                params.param(name.trim_end_matches('?')).filter(|value| !value.is_empty()).map(str::to_string)
+            }
+            None => Some(segment.to_string()),
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    // a pattern like `/:id?` still leaves the root
+    if path.is_empty() && pattern.starts_with('/') {
+        "/".to_string()
+    } else {
+        path
+    }
+}
+
+/// The params of `path` if it's a URL of the route `pattern`: `/contacts/alice` is
+/// one of `/contacts/:id`, with `id=alice`. A static segment of the pattern matches
+/// the path's wherever `same_segment(path's, pattern's)` says so.
+///
+/// Unlike the router's `Matcher`, this leaves the segments as they are rather than
+/// decoding them, which takes a browser, so it works in the prerender and in tests too.
+pub fn match_pattern(
+    pattern: &str,
+    path: &str,
+    same_segment: impl Fn(&str, &str) -> bool,
+) -> Option<BTreeMap<String, String>> {
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    let mut params = BTreeMap::new();
+    for part in pattern.split('/').filter(|s| !s.is_empty()) {
+        if let Some(name) = part.strip_prefix('*') {
+            params.insert(name.to_string(), segments.by_ref().collect::<Vec<_>>().join("/"));
+        } else {
+            let segment = segments.next()?;
+            match part.strip_prefix(':') {
+                Some(name) => _ = params.insert(name.to_string(), segment.to_string()),
+                None if same_segment(segment, part) => {}
+                None => return None,
+            }
+        }
+    }
+    // anything left over isn't this route, but one nested in it
+    segments.next().is_none().then_some(params)
+}
+
+/// A URL-safe id like a contact's: letters, digits, `-`, `_`, `.` and `~`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Id(pub String);
+
+impl FromStr for Id {
+    type Err = InvalidParam;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let safe = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '~');
+        if value.is_empty() || !value.chars().all(safe) {
+            return Err(InvalidParam::new(value, "an id"));
+        }
+        Ok(Self(value.to_string()))
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A path on this site, like `/contacts?q=bob`, but never a link elsewhere: a
+/// `//evil.example` is a protocol-relative URL, so it's turned down too, and so is
+/// `/\evil.example`, which browsers read as the same thing. Whitespace and control
+/// characters, which browsers strip out of URLs before reading them, aren't allowed
+/// anywhere in it either.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalPath(pub String);
+
+impl FromStr for LocalPath {
+    type Err = InvalidParam;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let unsafe_char = |c: char| c == '\\' || c.is_whitespace() || c.is_control();
+        if !value.starts_with('/') || value.starts_with("//") || value.contains(unsafe_char) {
+            return Err(InvalidParam::new(value, "a path on this site"));
+        }
+        Ok(Self(value.to_string()))
+    }
+}
+
+/// A whole number from `MIN` to `MAX`, both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct InRange<const MIN: i64, const MAX: i64>(pub i64);
+
+impl<const MIN: i64, const MAX: i64> FromStr for InRange<MIN, MAX> {
+    type Err = InvalidParam;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().parse::<i64>() {
+            Ok(number) if (MIN..=MAX).contains(&number) => Ok(Self(number)),
+            _ => Err(InvalidParam::new(value, format!("a whole number from {MIN} to {MAX}"))),
+        }
+    }
+}
+
+/// Comma separated values, like `?tags=family,work`. Blanks between commas are
+/// skipped, so `a,,b` and `a,b,` are both just `a` and `b`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct List<T>(pub Vec<T>);
+
+impl<T> FromStr for List<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Err = InvalidParam;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| item.parse::<T>().map_err(|error| InvalidParam::new(value, format!("a list: {error}"))))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(params: &[(&str, &str)]) -> BTreeMap<String, String> {
+        params.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn matches_params_and_wildcards() {
+        let same = |segment: &str, part: &str| segment == part;
+        assert_eq!(match_pattern("/contacts/:id", "/contacts/alice", same), Some(params(&[("id", "alice")])));
+        assert_eq!(match_pattern("/contacts/:id", "/contacts/alice/", same), Some(params(&[("id", "alice")])));
+        assert_eq!(match_pattern("/", "/", same), Some(params(&[])));
+        assert_eq!(
+            match_pattern("/people/:id/*tab", "/people/bob/conversations/1", same),
+            Some(params(&[("id", "bob"), ("tab", "conversations/1")]))
+        );
+        assert_eq!(match_pattern("/people/:id/*tab", "/people/bob", same), Some(params(&[("id", "bob"), ("tab", "")])));
+        // encoded segments stay encoded
+        assert_eq!(match_pattern("/tag/:tag", "/tag/a%20b", same), Some(params(&[("tag", "a%20b")])));
+    }
+
+    #[test]
+    fn other_routes_dont_match() {
+        let same = |segment: &str, part: &str| segment == part;
+        assert_eq!(match_pattern("/contacts/:id", "/contacts", same), None);
+        assert_eq!(match_pattern("/contacts/:id", "/contacts/alice/edit", same), None);
+        assert_eq!(match_pattern("/contacts/:id", "/people/alice", same), None);
+        // unless the static segments are the same in some other way
+        let translated = |segment: &str, part: &str| segment == part || (segment, part) == ("kontakte", "contacts");
+        assert_eq!(match_pattern("/contacts/:id", "/kontakte/alice", translated), Some(params(&[("id", "alice")])));
+    }
+
+    #[test]
+    fn fills_in_params() {
+        let alice = params(&[("id", "alice"), ("tab", "conversations/1")]);
+        assert_eq!(fill_in("/contacts/:id", &alice), "/contacts/alice");
+        assert_eq!(fill_in("/contacts/:id/*tab", &alice), "/contacts/alice/conversations/1");
+        assert_eq!(fill_in("/contacts/:id?", &alice), "/contacts/alice");
+        // a missing param leaves no empty segment behind
+        assert_eq!(fill_in("/tag/:tag", &alice), "/tag");
+        assert_eq!(fill_in("/contacts/:tag?/*rest", &alice), "/contacts");
+        assert_eq!(fill_in("/:tag?", &alice), "/");
+    }
+
+    #[test]
+    fn local_paths_stay_on_this_site() {
+        assert_eq!("/ok?x=1".parse(), Ok(LocalPath("/ok?x=1".to_string())));
+        assert_eq!("/".parse(), Ok(LocalPath("/".to_string())));
+        for elsewhere in ["//x", "/\\x", "http://x", "x", "", "/\t/x", "/ /x", "/x\ny", "/x y"] {
+            assert!(elsewhere.parse::<LocalPath>().is_err(), "{elsewhere:?} was let through");
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! Old URLs that still work after a route has been renamed.
+//!
+//! Each [`UrlRule`] maps a pattern to where its page lives now, with the same
+//! params: `/people/:id` to `/contacts/:id` sends `/people/alice?q=al` on to
+//! `/contacts/alice?q=al`. A redirect changes the URL (and the server answers
+//! with a redirect), while an alias leaves the URL alone and shows the page
+//! that's at the target, nested routes and all.
+//!
+//! The rules are checked when the routes are built, and whatever is wrong with
+//! them, like a redirect that ends up back where it started, is logged then.
+use std::collections::BTreeSet;
+
+use leptos::*;
+use leptos_router::*;
+
+use crate::locale::use_localize;
+use crate::params::{fill_in, match_pattern};
+
+/// What a [`UrlRule`] does with a URL that matches it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlRuleKind {
+    /// goes on to the new URL, without leaving the old one in the history
+    Redirect,
+    /// shows the page at the new URL, at the old one
+    Alias,
+}
+
+/// One old URL pattern, and the one it's known by now.
+#[derive(Debug, Clone, Copy)]
+pub struct UrlRule {
+    /// like `/people/:id`
+    pub from: &'static str,
+    /// like `/contacts/:id`. it can only use the params `from` has. an alias has
+    /// to point at a top-level route, like `/contacts`, and the routes nested in
+    /// it come along
+    pub to: &'static str,
+    pub kind: UrlRuleKind,
+}
+
+impl UrlRule {
+    pub const fn redirect(from: &'static str, to: &'static str) -> Self {
+        Self { from, to, kind: UrlRuleKind::Redirect }
+    }
+
+    pub const fn alias(from: &'static str, to: &'static str) -> Self {
+        Self { from, to, kind: UrlRuleKind::Alias }
+    }
+}
+
+/// A <Route/> for each rule, to go in the same list as `routes`, which the
+/// aliases are copied from. Anything wrong with the rules is logged.
+pub fn rule_routes(rules: &[UrlRule], routes: &[View]) -> Vec<View> {
+    let definitions = routes
+        .iter()
+        .filter_map(|route| route.as_transparent()?.downcast_ref::<RouteDefinition>().cloned())
+        .collect::<Vec<_>>();
+    for problem in check_rules(rules, &definitions) {
+        logging::error!("URL rules: {problem}");
+    }
+
+    rules
+        .iter()
+        .filter_map(|rule| match rule.kind {
+            UrlRuleKind::Redirect => {
+                let to = rule.to;
+                Some(view! { <Route path=rule.from view=move || view! { <RuleRedirect to/> }/> }.into_view())
+            }
+            UrlRuleKind::Alias => {
+                let mut definition = definitions.iter().find(|definition| definition.path == rule.to)?.clone();
+                definition.path = rule.from.to_string();
+                Some(definition.into_view())
+            }
+        })
+        .collect()
+}
+
+// sends the matched URL on to `to`, with its params filled in and the query and fragment kept
+#[component]
+fn RuleRedirect(to: &'static str) -> impl IntoView {
+    let params = use_params_map();
+    let location = use_location();
+    let search = location.search.get_untracked();
+    // a rule matched under a locale's prefix goes on to the same locale
+    let path = format!(
+        "{}{}{}",
+        use_localize()(&params.with_untracked(|params| fill_in(to, params))),
+        if search.is_empty() { String::new() } else { format!("?{search}") },
+        location.hash.get_untracked(),
+    );
+    // replaced, so going back doesn't land on the old URL and get redirected all over again
+    view! { <Redirect path options=NavigateOptions { replace: true, ..Default::default() }/> }
+}
+
+/// Whatever is wrong with `rules`, given the top-level routes they're added to.
+pub fn check_rules(rules: &[UrlRule], routes: &[RouteDefinition]) -> Vec<String> {
+    let mut problems = vec![];
+    for rule in rules {
+        let missing = params_of(rule.to).difference(&params_of(rule.from)).cloned().collect::<Vec<_>>();
+        if !missing.is_empty() {
+            problems.push(format!("{} → {} uses :{}, which {} doesn't have", rule.from, rule.to, missing.join(", :"), rule.from));
+        }
+        if routes.iter().any(|route| route.path == rule.from) {
+            problems.push(format!("{} is a route of its own, so the rule for it never applies", rule.from));
+        }
+        if rule.kind == UrlRuleKind::Alias && !routes.iter().any(|route| route.path == rule.to) {
+            problems.push(format!("the alias {} → {} doesn't point at a top-level route", rule.from, rule.to));
+        }
+        if let Some(chain) = redirect_loop(rules, rule) {
+            problems.push(format!("redirects go round in a loop: {}", chain.join(" → ")));
+        }
+    }
+    problems
+}
+
+// follows the redirects from `rule` on, and if that comes back around, the URLs it went through
+fn redirect_loop(rules: &[UrlRule], rule: &UrlRule) -> Option<Vec<String>> {
+    let mut chain = vec![rule.from.to_string()];
+    // the patterns work as URLs here, with `:id` as the id
+    let mut at = rule.to.to_string();
+    while !chain.contains(&at) {
+        chain.push(at.clone());
+        // anything else isn't redirected, so the chain ends there
+        let (next, params) = rules.iter().filter(|rule| rule.kind == UrlRuleKind::Redirect).find_map(|rule| {
+            let params = match_pattern(rule.from, &at, |segment, part| segment == part)?;
+            Some((rule, params))
+        })?;
+        at = fill_in(next.to, &params);
+    }
+    chain.push(at);
+    Some(chain)
+}
+
+// the names of the params and wildcards in a pattern
+fn params_of(pattern: &str) -> BTreeSet<String> {
+    pattern
+        .split('/')
+        .filter_map(|segment| segment.strip_prefix(':').or_else(|| segment.strip_prefix('*')))
+        .map(|name| name.trim_end_matches('?').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(chain: &[&str]) -> Option<Vec<String>> {
+        Some(chain.iter().map(|url| url.to_string()).collect())
+    }
+
+    #[test]
+    fn a_redirect_to_itself_is_a_loop() {
+        let rules = [UrlRule::redirect("/a", "/a")];
+        assert_eq!(redirect_loop(&rules, &rules[0]), chain(&["/a", "/a"]));
+        let rules = [UrlRule::redirect("/people/:id", "/people/:id")];
+        assert_eq!(redirect_loop(&rules, &rules[0]), chain(&["/people/:id", "/people/:id"]));
+    }
+
+    #[test]
+    fn a_chain_that_comes_back_around_is_a_loop() {
+        let rules = [UrlRule::redirect("/a", "/b"), UrlRule::redirect("/b", "/c"), UrlRule::redirect("/c", "/a")];
+        assert_eq!(redirect_loop(&rules, &rules[0]), chain(&["/a", "/b", "/c", "/a"]));
+        assert_eq!(redirect_loop(&rules, &rules[1]), chain(&["/b", "/c", "/a", "/b"]));
+        // through params and wildcards too
+        let rules = [
+            UrlRule::redirect("/people/:id/*tab", "/contacts/:id/*tab"),
+            UrlRule::redirect("/contacts/:id/*tab", "/people/:id/*tab"),
+        ];
+        assert_eq!(
+            redirect_loop(&rules, &rules[0]),
+            chain(&["/people/:id/*tab", "/contacts/:id/*tab", "/people/:id/*tab"])
+        );
+    }
+
+    #[test]
+    fn a_chain_that_ends_is_not_a_loop() {
+        let rules = [
+            UrlRule::redirect("/a", "/b"),
+            UrlRule::redirect("/b", "/c"),
+            UrlRule::redirect("/people/:id", "/contacts/:id"),
+            UrlRule::redirect("/people", "/contacts"),
+        ];
+        assert!(rules.iter().all(|rule| redirect_loop(&rules, rule).is_none()));
+        // an alias shows the page, it doesn't send the URL anywhere
+        let rules = [UrlRule::redirect("/a", "/b"), UrlRule::alias("/b", "/a")];
+        assert_eq!(redirect_loop(&rules, &rules[0]), None);
+        // one that runs into a loop further on never ends either
+        let rules = [UrlRule::redirect("/a", "/b"), UrlRule::redirect("/b", "/c"), UrlRule::redirect("/c", "/b")];
+        assert_eq!(redirect_loop(&rules, &rules[0]), chain(&["/a", "/b", "/c", "/b"]));
+    }
+
+    #[test]
+    fn checks_the_params_and_loops() {
+        let rules = [
+            UrlRule::redirect("/people/:id", "/contacts/:id/*tab"),
+            UrlRule::redirect("/a", "/a"),
+            UrlRule::alias("/address-book", "/contacts"),
+        ];
+        assert_eq!(
+            check_rules(&rules, &[]),
+            [
+                "/people/:id → /contacts/:id/*tab uses :tab, which /people/:id doesn't have",
+                "redirects go round in a loop: /a → /a",
+                "the alias /address-book → /contacts doesn't point at a top-level route",
+            ]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use leptos::{leptos_dom::is_server, *};
+use leptos_router::*;
+use wasm_bindgen::{closure::Closure, JsCast, UnwrapThrowExt};
+
+/// Where the router keeps the current route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoutingMode {
+    /// in the path, like `/contacts/alice`. Needs a server that answers every
+    /// path with index.html, like `trunk serve` does
+    Path,
+    /// in the fragment, like `index.html#/contacts/alice`. Works from any static
+    /// file host, and straight from disk with `file://`
+    Hash,
+}
+
+impl RoutingMode {
+    /// The mode this page was built for: hash routing when it's opened from disk,
+    /// or when the page asks for it with `<html data-routing="hash">`.
+    pub fn detect() -> Self {
+        // the server renders paths; the browser takes it from there
+        if is_server() {
+            return RoutingMode::Path;
+        }
+        let from_disk = window().location().protocol().is_ok_and(|protocol| protocol == "file:");
+        let asked_for = document()
+            .document_element()
+            .and_then(|html| html.get_attribute("data-routing"))
+            .is_some_and(|routing| routing == "hash");
+        if from_disk || asked_for {
+            RoutingMode::Hash
+        } else {
+            RoutingMode::Path
+        }
+    }
+
+    /// Makes the next <Router/> use this mode. The router only looks for a history
+    /// when it's created, so this has to come before it.
+    ///
+    /// Nothing else changes between the modes: <A/> and <Form/> still take paths
+    /// like `/contacts`. In hash mode <A/> writes them out as `#/contacts`, and
+    /// the router turns a submit into `#/contacts` too.
+    pub fn provide(self) {
+        provide_context(self);
+        if self == RoutingMode::Hash {
+            let history = HashHistory::new();
+            provide_context(RouterIntegrationContext::new(history));
+            follow_hash_links(history);
+        }
+    }
+
+    /// The mode the page is routing in, as provided by [`RoutingMode::provide`].
+    pub fn current() -> Self {
+        use_context().unwrap_or(RoutingMode::Path)
+    }
+
+    /// The `href` for a link to `path`: `#/contacts` in hash mode. Anything but a
+    /// path on this site, like `?tab=1` or `mailto:`, is the same in both modes.
+    pub fn href(self, path: &str) -> String {
+        match self {
+            RoutingMode::Hash if path.starts_with('/') => format!("#{path}"),
+            _ => path.to_string(),
+        }
+    }
+}
+
+// the router only knows paths, so it would take a click on `#/contacts` for a click on
+// a link to index.html itself. this listens while the event is still on its way down to
+// the link, so it gets there before the router and navigates to the fragment instead
+fn follow_hash_links(history: HashHistory) {
+    let on_click = Closure::<dyn Fn(web_sys::MouseEvent)>::new(move |ev: web_sys::MouseEvent| {
+        // the same clicks the router leaves to the browser, like opening a new tab
+        let modified = ev.meta_key() || ev.alt_key() || ev.ctrl_key() || ev.shift_key();
+        if ev.default_prevented() || ev.button() != 0 || modified {
+            return;
+        }
+        let Some(link) = ev
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+            .and_then(|element| element.closest("a[href]").ok().flatten())
+        else {
+            return;
+        };
+        if link.has_attribute("target") || link.has_attribute("download") {
+            return;
+        }
+        let href = link.get_attribute("href").unwrap_or_default();
+        let Some(route) = href.strip_prefix('#').filter(|route| route.starts_with('/')) else {
+            return;
+        };
+        ev.prevent_default();
+        history.navigate(&LocationChange {
+            value: route.to_string(),
+            // clicking the link to the page that's open doesn't add to the history
+            replace: history.0.with_untracked(|location| location.value == route),
+            scroll: !link.has_attribute("noscroll"),
+            state: State(None),
+        });
+    });
+    let listen = move |add: bool| {
+        let callback = on_click.as_ref().unchecked_ref();
+        _ = if add {
+            window().add_event_listener_with_callback_and_bool("click", callback, true)
+        } else {
+            window().remove_event_listener_with_callback_and_bool("click", callback, true)
+        };
+    };
+    listen(true);
+    on_cleanup(move || listen(false));
+}
+
+/// A [`History`] that keeps the route in `location.hash`, so the server only
+/// ever sees the path of index.html.
+#[derive(Clone, Copy)]
+pub struct HashHistory(RwSignal<LocationChange>);
+
+impl HashHistory {
+    pub fn new() -> Self {
+        Self(create_rw_signal(Self::current()))
+    }
+
+    // the route in the fragment; no fragment at all is the home page
+    fn current() -> LocationChange {
+        let hash = window().location().hash().unwrap_or_default();
+        let route = hash.trim_start_matches('#');
+        let state = window()
+            .history()
+            .and_then(|history| history.state())
+            .ok()
+            .filter(|state| !state.is_null());
+        LocationChange {
+            value: if route.starts_with('/') { route.to_string() } else { "/".to_string() },
+            replace: true,
+            scroll: true,
+            state: State(state),
+        }
+    }
+}
+
+impl Default for HashHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl History for HashHistory {
+    fn location(&self) -> ReadSignal<LocationChange> {
+        // back, forward and editing the fragment by hand all end up here
+        let location = self.0;
+        window_event_listener_untyped("popstate", move |_| location.set(Self::current()));
+        location.read_only()
+    }
+
+    fn navigate(&self, change: &LocationChange) {
+        let history = window().history().unwrap_throw();
+        let url = format!("#{}", change.value);
+        let state = change.state.to_js_value();
+        if change.replace {
+            history.replace_state_with_url(&state, "", Some(&url)).unwrap_throw();
+        } else {
+            history.push_state_with_url(&state, "", Some(&url)).unwrap_throw();
+        }
+        self.0.set(change.clone());
+        if change.scroll {
+            window().scroll_to_with_x_and_y(0.0, 0.0);
+        }
+    }
+}
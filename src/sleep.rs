@@ -0,0 +1,8 @@
+/// Waits `millis` milliseconds: with a browser timer in the browser, and with
+/// tokio on the server, where resources load too when the page is rendered there.
+pub async fn sleep(millis: u32) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::TimeoutFuture::new(millis).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(std::time::Duration::from_millis(millis.into())).await;
+}
@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use leptos::{html::Div, *};
+use leptos_router::*;
+
+// a little longer than the animations in style.css, for when `animationend` never fires
+const LEAVE_TIMEOUT: Duration = Duration::from_millis(400);
+
+/// How a <TransitionOutlet/> swaps one view for the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutletAnimation {
+    /// sideways, for tabs. only a different child route counts as a change
+    Slide,
+    /// in place, for details. the same route with other params, like another
+    /// contact, counts too
+    Fade,
+}
+
+impl OutletAnimation {
+    fn class(self) -> &'static str {
+        match self {
+            OutletAnimation::Slide => "slide",
+            OutletAnimation::Fade => "fade",
+        }
+    }
+}
+
+/// An <Outlet/> that animates from one child route to the next.
+///
+/// The view that's leaving stays mounted, on top, until its exit animation has
+/// finished, while the next one plays its enter animation underneath. The
+/// animations themselves are in style.css. When the system asks for reduced
+/// motion, views are swapped straight away, like a plain <Outlet/> does.
+///
+/// The router keeps the view of a route whose params change, so when a Fade
+/// goes from one contact to another, there's no old view to fade out, and the
+/// contact just fades in.
+#[component]
+pub fn TransitionOutlet(
+    /// slide or fade
+    animation: OutletAnimation,
+) -> impl IntoView {
+    let route = use_route();
+    let pathname = use_location().pathname;
+    let build_outlet = as_child_of_current_owner(|child: RouteContext| {
+        provide_context(child.clone());
+        child.outlet().into_view()
+    });
+
+    let (current, set_current) = create_signal(None::<View>);
+    let (leaving, set_leaving) = create_signal(None::<View>);
+    // each view's owner, kept until the view is gone from the page
+    let current_disposer = store_value(None::<Disposer>);
+    let leaving_disposer = store_value(None::<Disposer>);
+    // how many views have left so far, so a timeout only ever takes down its own
+    let leaves = store_value(0_usize);
+    let current_ref = create_node_ref::<Div>();
+
+    let finish_leaving = move || {
+        set_leaving(None);
+        leaving_disposer.set_value(None);
+    };
+
+    // keeps track of which child route matched, by its pattern, and the path it matched.
+    // isomorphic, so the server renders the first view too
+    create_isomorphic_effect(move |prev: Option<Option<(String, String)>>| {
+        pathname.track();
+        let child = route.child();
+        let matched = child.as_ref().map(|child| (child.original_path().to_string(), child.path()));
+
+        let Some(prev) = prev else {
+            // the page as it was loaded: nothing to animate
+            let (view, disposer) = child.map(&build_outlet).unzip();
+            set_current(view);
+            current_disposer.set_value(disposer);
+            return matched;
+        };
+        let same_route = prev.as_ref().map(|(pattern, _)| pattern) == matched.as_ref().map(|(pattern, _)| pattern);
+        if same_route {
+            if animation == OutletAnimation::Fade && prev != matched {
+                play_enter(current_ref);
+            }
+            return matched;
+        }
+
+        let (view, disposer) = child.map(&build_outlet).unzip();
+        // the old view leaves the page first, so it can be put back as the one that's leaving
+        let old = current.get_untracked();
+        set_current(view);
+        let old_disposer = current_disposer.try_update_value(|current| std::mem::replace(current, disposer)).flatten();
+        // a view that was still leaving goes right away
+        finish_leaving();
+        if old.is_some() && !prefers_reduced_motion() {
+            set_leaving(old);
+            leaving_disposer.set_value(old_disposer);
+            leaves.update_value(|leaves| *leaves += 1);
+            let this_leave = leaves.get_value();
+            set_timeout(
+                // unless another navigation has put a different view there since
+                move || {
+                    if leaves.get_value() == this_leave {
+                        finish_leaving();
+                    }
+                },
+                LEAVE_TIMEOUT,
+            );
+        }
+        play_enter(current_ref);
+        matched
+    });
+
+    view! {
+        <div class=format!("transition-outlet {}", animation.class())>
+            {move || leaving().map(|view| view! {
+                <div
+                    class="outlet-leave"
+                    inert
+                    // the animations of whatever is inside the view bubble up here too
+                    on:animationend=move |ev| if ev.target() == ev.current_target() { finish_leaving() }
+                >
+                    {view}
+                </div>
+            })}
+            <div class="outlet-current" node_ref=current_ref>{current}</div>
+        </div>
+    }
+}
+
+// restarts the enter animation, which only plays when its class is added
+fn play_enter(current: NodeRef<Div>) {
+    let Some(current) = current.get_untracked() else { return };
+    let classes = current.class_list();
+    _ = classes.remove_1("outlet-enter");
+    // reading the layout makes the browser notice the class was gone
+    _ = current.offset_width();
+    _ = classes.add_1("outlet-enter");
+}
+
+fn prefers_reduced_motion() -> bool {
+    let query = window().match_media("(prefers-reduced-motion: reduce)").ok().flatten();
+    query.is_some_and(|query| query.matches())
+}
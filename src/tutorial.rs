@@ -0,0 +1,135 @@
+use leptos::*;
+use leptos_router::*;
+
+use crate::{
+    apps,
+    auth::{protect, provide_session, LoginPage, SessionStatus},
+    breadcrumbs::{provide_breadcrumbs, with_breadcrumb, Breadcrumbs},
+    components,
+    loaders::{provide_loaders, Prefetch},
+    locale::{localize_routes, LocaleSwitcher, A},
+    navigation_focus::NavigationFocus,
+    navigation_progress::NavigationProgress,
+    not_found::NotFoundPage,
+    redirects::{rule_routes, UrlRule},
+    routing::RoutingMode,
+};
+
+/// URLs that have been renamed, so bookmarks of the old ones keep working.
+static URL_RULES: &[UrlRule] = &[
+    // the address book used to be called "people"
+    UrlRule::redirect("/people", "/contacts"),
+    UrlRule::redirect("/people/:id", "/contacts/:id"),
+    UrlRule::redirect("/people/:id/*tab", "/contacts/:id/*tab"),
+    // both names stay in use for the address book
+    UrlRule::alias("/address-book", "/contacts"),
+];
+
+/// The whole tutorial: the providers, the nav and every route.
+///
+/// `main.rs` mounts it in the browser, and `server/` renders it to HTML.
+#[component]
+pub fn RouteManager() -> impl IntoView {
+    // path or hash routing, whichever the page was built for
+    RoutingMode::detect().provide();
+    // who is signed in, for the routes that need a login
+    provide_session();
+    // the trail of the routes that are matched right now
+    provide_breadcrumbs();
+    // what the routes have loaded, and what a hovered link should load
+    provide_loaders();
+    components::provide_example_prefetches();
+    // the routes of every registered app read their data from contexts provided here
+    for app in apps::REGISTRY {
+        (app.provide)();
+        (app.prefetch)();
+    }
+
+    // <Routes/> only looks at its direct children, so the app routes are added to the list
+    // one by one rather than as a single nested fragment
+    let mut routes = vec![
+        // / just has an un-nested "home"
+        view! { <Route path="/" view=Home/> }.into_view(),
+        // route to the form example
+        with_breadcrumb(
+            view! { <Route path="/form" view=components::form_component::FormComponent/> },
+            "Form".into(),
+        ),
+        // every tutorial example, each on its own route
+        components::ExampleRoutes().into_view(),
+        // every registered app, as tiles
+        with_breadcrumb(view! { <Route path="/apps" view=apps::Launcher/> }, "Apps".into()),
+        // where protected routes send you, with ?next= to come back
+        with_breadcrumb(view! { <Route path="/login" view=LoginPage/> }, "Log in".into()),
+    ];
+    // each app brings its own nested routes, e.g. contact's create/edit forms
+    for app in apps::REGISTRY {
+        routes.extend((app.routes)().into_iter().map(|route| {
+            if app.requires_login {
+                protect(route, None)
+            } else {
+                route
+            }
+        }));
+    }
+    // the URLs that were renamed, which still lead to the same pages
+    let rules = rule_routes(URL_RULES, &routes);
+    routes.extend(rules);
+    // anything else is a 404. the wildcard loses to every other route, so it goes last
+    routes.push(with_breadcrumb(
+        view! { <Route path="/*any" view=|| view! { <NotFoundPage paths=known_paths() /> }/> },
+        "Not found".into(),
+    ));
+    // and all of them again under each locale's prefix, like /de/kontakte. the links
+    // in the nav need to know those URLs too, so this happens up here
+    let routes = localize_routes(routes);
+
+    // set by the router from the moment a link is followed until the new page has loaded
+    let (is_routing, set_is_routing) = create_signal(false);
+
+    view! {
+        <Router set_is_routing>
+            <NavigationProgress is_routing />
+            <NavigationFocus is_routing />
+            <Prefetch/>
+            <h1>"Leptos Examples"</h1>
+            // this <nav> will show on every routes, because its outside the <Routes/>
+            // note: we can just use normal <a> tags and the router will use client-side navigation
+            <nav>
+                <A href="/">"Home"</A>
+                <A href="/examples">"Examples"</A>
+                <A href="/form">"Form"</A>
+                <A href="/apps">"Apps"</A>
+                {apps::REGISTRY.iter().map(|app| view! {
+                    <A href=app.base_path>{app.icon}" "{app.name}</A>
+                }).collect_view()}
+                <SessionStatus/>
+                <LocaleSwitcher/>
+            </nav>
+            <Breadcrumbs/>
+            <main>
+                <Routes children=Box::new(move || Fragment::new(routes)) />
+            </main>
+        </Router>
+    }
+}
+
+// every page the 404 page might suggest instead
+fn known_paths() -> Vec<String> {
+    ["/", "/examples", "/form", "/apps", "/login"]
+        .into_iter()
+        .chain(apps::REGISTRY.iter().flat_map(|app| app.pages.iter().copied()))
+        .map(str::to_string)
+        .chain(components::example_paths())
+        .collect()
+}
+
+#[component]
+fn Home() -> impl IntoView {
+    // every example used to be mounted right here at once. now each one has its own
+    // route, so only the one you're looking at runs its effects and resources
+    view! {
+        <h2>"Examples"</h2>
+        <components::ExampleSidebar/>
+    }
+}